@@ -0,0 +1,92 @@
+// Regenerates the preflop equity tables shipped with the crate:
+// `cargo run --release --example preflop_equity > src/preflop_equity.csv`
+//
+// Every starting hand is dealt against 1 to 9 random hands with a full board, the random hands
+// are dealt from a shuffled deck so every two cards are as likely as in a real deal.
+use poker_prob::preflop::{starting_hand_name, MAX_RANDOM_OPPONENTS};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rs_poker::core::{Card, Rankable, Suit, Value};
+use std::thread;
+
+const ITERATIONS: u32 = 200_000;
+
+// Percent of the deals we win and tie outright against the random hands
+fn simulate(hand: [Card; 2], num_opponents: usize, seed: u64) -> (f64, f64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut deck: Vec<Card> = Value::values()
+        .iter()
+        .flat_map(|&value| Suit::suits().map(|suit| Card::new(value, suit)))
+        .filter(|card| !hand.contains(card))
+        .collect();
+
+    let mut wins: u32 = 0;
+    let mut ties: u32 = 0;
+    let mut all_cards: Vec<Card> = Vec::with_capacity(7);
+    for _ in 0..ITERATIONS {
+        let (dealt, _) = deck.partial_shuffle(&mut rng, 5 + 2 * num_opponents);
+        let (board, opponents) = dealt.split_at(5);
+
+        all_cards.clear();
+        all_cards.extend_from_slice(&hand);
+        all_cards.extend_from_slice(board);
+        let my_rank = all_cards.rank();
+
+        let mut best_opponent_rank = None;
+        for cards in opponents.chunks(2) {
+            all_cards.clear();
+            all_cards.extend_from_slice(cards);
+            all_cards.extend_from_slice(board);
+            best_opponent_rank = best_opponent_rank.max(Some(all_cards.rank()));
+        }
+        match best_opponent_rank {
+            Some(rank) if rank > my_rank => {}
+            Some(rank) if rank == my_rank => ties += 1,
+            _ => wins += 1,
+        }
+    }
+    (
+        wins as f64 * 100.0 / ITERATIONS as f64,
+        ties as f64 * 100.0 / ITERATIONS as f64,
+    )
+}
+
+fn main() {
+    // One suited, offsuit or paired combo for each of the 169 starting hands
+    let values: Vec<Value> = Value::values().into_iter().rev().collect();
+    let mut hands: Vec<[Card; 2]> = Vec::new();
+    for (i, &high) in values.iter().enumerate() {
+        for &low in &values[i..] {
+            if high == low {
+                hands.push([Card::new(high, Suit::Spade), Card::new(low, Suit::Heart)]);
+            } else {
+                hands.push([Card::new(high, Suit::Heart), Card::new(low, Suit::Heart)]);
+                hands.push([Card::new(high, Suit::Heart), Card::new(low, Suit::Diamond)]);
+            }
+        }
+    }
+
+    let handles: Vec<_> = hands
+        .iter()
+        .enumerate()
+        .map(|(i, &hand)| {
+            thread::spawn(move || {
+                (1..=MAX_RANDOM_OPPONENTS)
+                    .map(|num_opponents| {
+                        let seed = (i * MAX_RANDOM_OPPONENTS + num_opponents) as u64;
+                        simulate(hand, num_opponents, seed)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    println!("hand,opponents,win,tie");
+    for (hand, handle) in hands.iter().zip(handles) {
+        let name = starting_hand_name(hand[0], hand[1]);
+        for (i, (win, tie)) in handle.join().unwrap().into_iter().enumerate() {
+            println!("{},{},{:.2},{:.2}", name, i + 1, win, tie);
+        }
+    }
+}
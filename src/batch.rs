@@ -0,0 +1,354 @@
+use crate::calc_prob::{self, HandRank, StraightDraw, Street};
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::json::Scenario;
+use crate::report::{analyze, Report};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Format of the scenarios read and the results written by the batch mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchFormat {
+    /// Comma separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl BatchFormat {
+    // Function to guess the format from the extension of a file
+    pub fn from_path(path: &str) -> Option<BatchFormat> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(BatchFormat::Csv),
+            "ndjson" | "jsonl" | "json" => Some(BatchFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+// A CSV row can't hold a list, the ranges of the opponents are separated by `|` in one column
+#[derive(Debug, Deserialize)]
+struct CsvScenario {
+    game: Option<Game>,
+    hand: String,
+    #[serde(default)]
+    board: String,
+    #[serde(default)]
+    dead_cards: String,
+    all_in: Option<bool>,
+    #[serde(default)]
+    villains: String,
+    pot: Option<f64>,
+    bet: Option<f64>,
+    effective_stack: Option<f64>,
+    draw_target: Option<HandRank>,
+}
+
+impl From<CsvScenario> for Scenario {
+    fn from(csv_scenario: CsvScenario) -> Self {
+        Scenario {
+            game: csv_scenario.game.unwrap_or_default(),
+            hand: csv_scenario.hand,
+            board: csv_scenario.board,
+            dead_cards: csv_scenario.dead_cards,
+            all_in: csv_scenario.all_in.unwrap_or(false),
+            villains: csv_scenario
+                .villains
+                .split('|')
+                .map(|villain| villain.trim().to_string())
+                .filter(|villain| !villain.is_empty())
+                .collect(),
+            pot: csv_scenario.pot,
+            bet: csv_scenario.bet,
+            effective_stack: csv_scenario.effective_stack,
+            draw_target: csv_scenario.draw_target,
+        }
+    }
+}
+
+/// Result of one scenario, the fields that can't be calculated are left empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BatchRow {
+    /// Number of the scenario in the input, starting at 1.
+    pub scenario: usize,
+    pub hand: String,
+    pub board: String,
+    pub street: Option<Street>,
+    pub current_hand_rank: Option<HandRank>,
+    pub unique_outs: Option<usize>,
+    pub improve_four_and_two_percent: Option<i8>,
+    pub improve_exact_percent: Option<f64>,
+    pub straight_draw: Option<StraightDraw>,
+    pub required_equity: Option<f64>,
+    pub call: Option<bool>,
+    pub ev_of_calling: Option<f64>,
+    pub equity_win: Option<f64>,
+    pub equity_tie: Option<f64>,
+    pub equity_lose: Option<f64>,
+    /// Why the scenario could not be analyzed.
+    pub error: Option<String>,
+}
+
+impl BatchRow {
+    fn from_report(scenario: usize, report: &Report) -> BatchRow {
+        BatchRow {
+            scenario,
+            hand: cards_to_string(&report.hand),
+            board: cards_to_string(&report.board),
+            street: Some(report.street),
+            current_hand_rank: Some(report.current_hand_rank),
+            unique_outs: Some(report.combined_outs.values().map(|cards| cards.len()).sum()),
+            improve_four_and_two_percent: Some(report.improve_prob),
+            improve_exact_percent: Some(report.exact_improve_prob),
+            straight_draw: Some(report.straight_draw),
+            required_equity: report.pot_odds.map(|pot_odds| pot_odds.required_equity),
+            call: report.pot_odds.map(|pot_odds| pot_odds.call),
+            ev_of_calling: report.pot_odds.map(|pot_odds| pot_odds.ev_of_calling),
+            equity_win: report.equity.map(|equity| equity.win),
+            equity_tie: report.equity.map(|equity| equity.tie),
+            equity_lose: report.equity.map(|equity| equity.lose),
+            error: None,
+        }
+    }
+
+    fn from_error(scenario: usize, hand: &str, board: &str, error: String) -> BatchRow {
+        BatchRow {
+            scenario,
+            hand: hand.to_string(),
+            board: board.to_string(),
+            error: Some(error),
+            ..BatchRow::default()
+        }
+    }
+}
+
+pub(crate) fn cards_to_string(cards: &[rs_poker::core::Card]) -> String {
+    cards.iter().map(|card| card.to_string()).collect()
+}
+
+// Function to read every scenario, a line or row that can't be read keeps its place with the reason
+pub fn read_scenarios<R: Read>(
+    reader: R,
+    format: BatchFormat,
+) -> Result<Vec<Result<Scenario, String>>, PokerProbError> {
+    let mut scenarios = Vec::new();
+    match format {
+        BatchFormat::Csv => {
+            let mut csv_reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(reader);
+            for record in csv_reader.deserialize::<CsvScenario>() {
+                scenarios.push(
+                    record
+                        .map(Scenario::from)
+                        .map_err(|err| format!("Invalid row: {}", err)),
+                );
+            }
+        }
+        BatchFormat::Ndjson => {
+            for line in BufReader::new(reader).lines() {
+                let line = line.map_err(|err| PokerProbError::Io(err.to_string()))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                scenarios.push(
+                    serde_json::from_str::<Scenario>(&line)
+                        .map_err(|err| format!("Invalid line: {}", err)),
+                );
+            }
+        }
+    }
+    Ok(scenarios)
+}
+
+// Function to analyze one scenario with the iterations and seed shared by the whole batch
+pub fn analyze_scenario(
+    scenario: &Scenario,
+    iterations: u32,
+    seed: u64,
+) -> Result<Report, PokerProbError> {
+    let hand = calc_prob::parse_cards(&scenario.hand)?;
+    let board = calc_prob::parse_cards(&scenario.board)?;
+    analyze(&hand, &board, &scenario.to_options(iterations, seed)?)
+}
+
+// Function to analyze every scenario and write one result row for each of them, returns the number of rows
+pub fn run_batch<R: Read, W: Write>(
+    reader: R,
+    writer: W,
+    input_format: BatchFormat,
+    output_format: BatchFormat,
+    iterations: u32,
+    seed: u64,
+) -> Result<usize, PokerProbError> {
+    let rows: Vec<BatchRow> = read_scenarios(reader, input_format)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, scenario)| match scenario {
+            Ok(scenario) => match analyze_scenario(&scenario, iterations, seed) {
+                Ok(report) => BatchRow::from_report(i + 1, &report),
+                Err(err) => {
+                    BatchRow::from_error(i + 1, &scenario.hand, &scenario.board, err.to_string())
+                }
+            },
+            Err(err) => BatchRow::from_error(i + 1, "", "", err),
+        })
+        .collect();
+
+    write_rows(writer, &rows, output_format)?;
+    Ok(rows.len())
+}
+
+// Function to write one CSV row or JSON line per result, shared with the replay of hand histories
+pub(crate) fn write_rows<W: Write, T: Serialize>(
+    writer: W,
+    rows: &[T],
+    format: BatchFormat,
+) -> Result<(), PokerProbError> {
+    let io_error = |err: std::io::Error| PokerProbError::Io(err.to_string());
+    match format {
+        BatchFormat::Csv => {
+            let mut csv_writer = csv::Writer::from_writer(writer);
+            for row in rows {
+                csv_writer
+                    .serialize(row)
+                    .map_err(|err| PokerProbError::Io(err.to_string()))?;
+            }
+            csv_writer.flush().map_err(io_error)?;
+        }
+        BatchFormat::Ndjson => {
+            let mut writer = writer;
+            for row in rows {
+                let line = serde_json::to_string(row).expect("Row should serialize to JSON.");
+                writeln!(writer, "{}", line).map_err(io_error)?;
+            }
+            writer.flush().map_err(io_error)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(BatchFormat::from_path("spots.csv"), Some(BatchFormat::Csv));
+        assert_eq!(
+            BatchFormat::from_path("session/spots.ndjson"),
+            Some(BatchFormat::Ndjson)
+        );
+        assert_eq!(BatchFormat::from_path("spots.txt"), None);
+        assert_eq!(BatchFormat::from_path("spots"), None);
+    }
+
+    #[test]
+    fn test_read_csv_scenarios() {
+        let input = "hand,board,all_in,villains,pot,bet,effective_stack\n\
+                     AhKh,7h6h2c,false,\"TT+,AKs|2s2d\",100,50,\n\
+                     9h8h,7h6h2c,true,,,,\n\
+                     7s7d,,,,,,\n";
+        let scenarios = read_scenarios(input.as_bytes(), BatchFormat::Csv).unwrap();
+        assert_eq!(scenarios.len(), 3);
+
+        let first = scenarios[0].as_ref().unwrap();
+        assert_eq!(first.villains, vec!["TT+,AKs", "2s2d"]);
+        assert_eq!(first.pot, Some(100.0));
+        assert_eq!(first.effective_stack, None);
+
+        let second = scenarios[1].as_ref().unwrap();
+        assert!(second.all_in);
+        assert!(second.villains.is_empty());
+
+        // Empty columns keep their defaults
+        let third = scenarios[2].as_ref().unwrap();
+        assert!(!third.all_in);
+        assert_eq!(third.game, Game::Holdem);
+        assert_eq!(third.board, "");
+    }
+
+    #[test]
+    fn test_read_ndjson_scenarios() {
+        let input = "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"villains\": [\"2s2d\"]}\n\
+                     \n\
+                     not json\n\
+                     {\"hand\": \"7s7d\"}\n";
+        let scenarios = read_scenarios(input.as_bytes(), BatchFormat::Ndjson).unwrap();
+        assert_eq!(scenarios.len(), 3);
+        assert_eq!(scenarios[0].as_ref().unwrap().villains, vec!["2s2d"]);
+        assert!(scenarios[1].is_err());
+        assert_eq!(scenarios[2].as_ref().unwrap().board, "");
+    }
+
+    // A scenario that can't be analyzed gets a row with the error, the others are still analyzed
+    #[test]
+    fn test_run_batch() {
+        let input = "hand,board\n9h8h,7h6h2c\nAhAh,7h6h2c\n";
+        let mut output: Vec<u8> = Vec::new();
+        let num_rows = run_batch(
+            input.as_bytes(),
+            &mut output,
+            BatchFormat::Csv,
+            BatchFormat::Csv,
+            100,
+            1,
+        )
+        .unwrap();
+        assert_eq!(num_rows, 2);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("scenario,hand,board,street,current_hand_rank"));
+        assert!(lines[1].starts_with("1,9h8h,7h6h2c,flop,high_card,29,"));
+        assert!(lines[2].ends_with("Ah is used more than once"));
+    }
+
+    #[test]
+    fn test_run_batch_ndjson_output() {
+        let input = "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"pot\": 100, \"bet\": 50}\n";
+        let mut output: Vec<u8> = Vec::new();
+        run_batch(
+            input.as_bytes(),
+            &mut output,
+            BatchFormat::Ndjson,
+            BatchFormat::Ndjson,
+            100,
+            1,
+        )
+        .unwrap();
+
+        let row: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(row["scenario"], 1);
+        assert_eq!(row["call"], true);
+        assert!(row["error"].is_null());
+    }
+
+    // A bet of 0 has no price to call, the row gets the error instead of a NaN
+    #[test]
+    fn test_run_batch_invalid_bet() {
+        let input = "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"pot\": 0, \"bet\": 0}\n\
+                     {\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"pot\": -100, \"bet\": 50}\n";
+        let mut output: Vec<u8> = Vec::new();
+        run_batch(
+            input.as_bytes(),
+            &mut output,
+            BatchFormat::Ndjson,
+            BatchFormat::Ndjson,
+            100,
+            1,
+        )
+        .unwrap();
+
+        let rows: Vec<serde_json::Value> = output
+            .split(|&byte| byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(rows[0]["error"], "Bet should be more than 0, got 0");
+        assert!(rows[0]["required_equity"].is_null());
+        assert_eq!(rows[1]["error"], "Pot should be 0 or more, got -100");
+    }
+}
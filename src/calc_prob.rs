@@ -1,38 +1,201 @@
-use rs_poker::core::{Card, Deck, Hand, Rankable, Suit, Value};
-use std::collections::HashMap;
+use crate::error::PokerProbError;
+use crate::game::Game;
+use rs_poker::core::{Card, CardIter, Deck, Hand, Rank, Rankable, Suit, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+// Function to apply the rule of 4 and 2, capped at 100% since Omaha can have more than 25 outs
+pub fn calc_4_and_2_probs(all_in: bool, num_community_cards: usize, outs: i32) -> i8 {
+    if outs < 0 {
+        return 0;
+    }
+
+    let multiplier = if num_community_cards >= 4 && all_in {
+        4
+    } else {
+        2
+    };
+    outs.saturating_mul(multiplier).min(100) as i8
+}
 
-pub fn calc_4_and_2_probs(all_in: bool, num_community_cards: i8, outs: i8) -> i8 {
+// Function to apply the rule of 2 to every card still to come, for games that deal more than 2 cards like stud
+pub fn calc_rule_of_2_probs(num_cards_to_come: usize, outs: i32) -> i8 {
     if outs < 0 {
         return 0;
     }
+    outs.saturating_mul(2 * num_cards_to_come as i32).min(100) as i8
+}
 
-    if num_community_cards >= 4 {
-        if all_in {
-            return outs * 4;
+// Function to enumerate every possible runout from the unknown cards and calculate the exact
+// probability (in percent) of finishing with each hand rank
+pub fn calc_exact_probs(
+    game: Game,
+    all_in: bool,
+    deck: &Deck,
+    hand: &Hand,
+    community: &Hand,
+) -> HashMap<HandRank, f64> {
+    let num_cards_to_come = game.num_cards_to_come(all_in, hand, community);
+
+    // Sort the unknown cards so the enumeration does not depend on the hash order of the deck
+    let mut unknown_cards: Vec<Card> = deck.iter().copied().collect();
+    unknown_cards.sort();
+
+    let mut rank_counts: HashMap<HandRank, u32> = HashMap::new();
+    let mut num_runouts: u32 = 0;
+    let mut board: Vec<Card>;
+
+    if num_cards_to_come == 0 {
+        rank_counts.insert(game.hand_rank(hand.cards(), community.cards()), 1);
+        num_runouts = 1;
+    } else {
+        for runout in CardIter::new(&unknown_cards, num_cards_to_come) {
+            board = community.cards().to_vec();
+            board.extend(runout);
+            *rank_counts
+                .entry(game.hand_rank(hand.cards(), &board))
+                .or_insert(0) += 1;
+            num_runouts += 1;
         }
     }
-    outs * 2
+
+    let mut probs = HashMap::new();
+    for (hand_rank, count) in rank_counts {
+        probs.insert(hand_rank, count as f64 * 100.0 / num_runouts as f64);
+    }
+    probs
 }
 
-// Function to remove cards in hand and community from a brand new deck
-pub fn get_unknown_cards(hand: &Hand, community: &Hand) -> Deck {
-    // Initial deck with 52 cards
-    let mut deck = Deck::default();
+// Get the hand rank of the best hand made with the cards on table
+pub fn get_current_hand_rank(game: Game, hand: &Hand, community: &Hand) -> HandRank {
+    game.hand_rank(hand.cards(), community.cards())
+}
 
-    // Remove cards in hand from deck
-    let mut temp_card: Card;
-    for card in hand.cards() {
-        temp_card = card.clone();
-        deck.remove(temp_card);
+// Function to rank the best 5 cards on table, split into the cards making the hand rank and the kickers
+pub fn get_made_hand(game: Game, hand: &Hand, community: &Hand) -> MadeHand {
+    let hand_rank = get_current_hand_rank(game, hand, community);
+    let mut cards = game.best_five_cards(hand.cards(), community.cards());
+
+    // Sets of the same value first, from the biggest set and the highest value down
+    let mut card_values: HashMap<Value, usize> = HashMap::new();
+    for card in &cards {
+        *card_values.entry(card.value).or_insert(0) += 1;
+    }
+    cards.sort_by_key(|card| {
+        std::cmp::Reverse((card_values[&card.value], card.value, card.suit.to_char()))
+    });
+    // The ace of a wheel is the lowest card of the straight
+    let is_straight = matches!(hand_rank, HandRank::Straight | HandRank::StraightFlush);
+    if is_straight
+        && card_values.contains_key(&Value::Ace)
+        && !card_values.contains_key(&Value::King)
+    {
+        cards.rotate_left(1);
+    }
+
+    let num_cards_making_rank = match hand_rank {
+        HandRank::HighCard => 1,
+        HandRank::OnePair => 2,
+        HandRank::ThreeOfAKind => 3,
+        HandRank::TwoPair | HandRank::FourOfAKind => 4,
+        _ => 5,
+    };
+    let kickers = cards[num_cards_making_rank.min(cards.len())..].to_vec();
+    MadeHand {
+        hand_rank,
+        cards,
+        kickers,
+    }
+}
+
+// Go through every unknown card once and put it under the best hand rank it makes,
+// only keeping the cards that improve on the current hand rank
+pub fn calc_combined_outs(
+    game: Game,
+    deck: &Deck,
+    hand: &Hand,
+    community: &Hand,
+) -> BTreeMap<HandRank, Vec<Card>> {
+    let current_hand_rank = get_current_hand_rank(game, hand, community);
+    let mut board: Vec<Card> = community.cards().to_vec();
+
+    let mut unknown_cards: Vec<Card> = deck.iter().copied().collect();
+    unknown_cards.sort_by_key(|card| (card.value, card.suit.to_char()));
+
+    let mut combined_outs: BTreeMap<HandRank, Vec<Card>> = BTreeMap::new();
+    let mut hand_rank: HandRank;
+    for card in unknown_cards {
+        board.push(card);
+        hand_rank = game.hand_rank(hand.cards(), &board);
+        if game.strength(hand_rank) > game.strength(current_hand_rank) {
+            combined_outs.entry(hand_rank).or_default().push(card);
+        }
+        board.pop();
+    }
+
+    combined_outs
+}
+
+// Function to parse cards such as `AhKd`, every card is a value followed by a suit and can only be used once
+pub fn parse_cards(cards_str: &str) -> Result<Hand, PokerProbError> {
+    let chars: Vec<char> = cards_str.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut cards: Vec<Card> = Vec::new();
+
+    for card_chars in chars.chunks(2) {
+        let card = match card_chars {
+            [value, suit] => Value::from_char(*value)
+                .zip(Suit::from_char(*suit))
+                .map(|(value, suit)| Card::new(value, suit)),
+            _ => None,
+        }
+        .ok_or_else(|| PokerProbError::InvalidCard(card_chars.iter().collect()))?;
+
+        if cards.contains(&card) {
+            return Err(PokerProbError::DuplicateCard(card));
+        }
+        cards.push(card);
     }
 
-    // Remove community cards from deck
-    for card in community.cards() {
-        temp_card = card.clone();
-        deck.remove(temp_card);
+    Ok(Hand::new_with_cards(cards))
+}
+
+// Function to remove cards in hand, community and dead cards from a brand new deck of the game,
+// a card that is both in our hand and on the board can't be removed twice.
+// The dead cards are the cards seen elsewhere, e.g. the upcards of the other players in stud.
+pub fn get_unknown_cards(
+    game: Game,
+    hand: &Hand,
+    community: &Hand,
+    dead_cards: &[Card],
+) -> Result<Deck, PokerProbError> {
+    // Initial deck with 52 cards, or 36 in short deck
+    let mut deck = game.deck();
+
+    // Remove cards in hand, community cards and dead cards from deck
+    for card in hand
+        .cards()
+        .iter()
+        .chain(community.cards())
+        .chain(dead_cards)
+    {
+        remove_known_card(game, &mut deck, card)?;
     }
 
-    deck
+    Ok(deck)
+}
+
+// Function to remove a card we have seen from the deck, telling apart a card used twice from one the game doesn't deal
+fn remove_known_card(game: Game, deck: &mut Deck, card: &Card) -> Result<(), PokerProbError> {
+    if deck.remove(card) {
+        return Ok(());
+    }
+    if game.deck().contains(card) {
+        Err(PokerProbError::DuplicateCard(*card))
+    } else {
+        Err(PokerProbError::CardNotInDeck(game, *card))
+    }
 }
 
 // Given a hand, count the number of card with the same suit or value
@@ -40,7 +203,7 @@ fn count_suit_and_value_on_hand(
     hand: &Hand,
     card_suits: &mut HashMap<Suit, i8>,
     card_values: &mut HashMap<Value, i8>,
-) -> () {
+) {
     for card in hand.cards() {
         card_suits.entry(card.suit).or_insert(0);
         card_suits.insert(card.suit, card_suits[&card.suit] + 1);
@@ -65,114 +228,164 @@ fn count_suit_and_value_on_table(
     (card_suits, card_values)
 }
 
-fn get_one_pair_outs(hand: &Hand, community: &Hand) -> i8 {
-    let (_, card_values) = count_suit_and_value_on_table(&hand, &community);
+// Get the cards left in the deck with one of the given values, sorted so the outs are easy to read
+fn get_cards_with_values(deck: &Deck, values: &[Value]) -> Vec<Card> {
+    let mut cards: Vec<Card> = deck
+        .iter()
+        .filter(|card| values.contains(&card.value))
+        .copied()
+        .collect();
+    cards.sort_by_key(|card| (card.value, card.suit.to_char()));
+    cards
+}
+
+// Get the values of the cards in hand that are not paired with any other card on table
+fn get_unpaired_hand_values(hand: &Hand, card_values: &HashMap<Value, i8>) -> Vec<Value> {
+    hand.cards()
+        .iter()
+        .map(|card| card.value)
+        .filter(|value| card_values[value] == 1)
+        .collect()
+}
+
+pub fn get_one_pair_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
 
     for (_, &count) in card_values.iter() {
-        // If count of values of cards on table == 2, that means we already a pair, return outs = 0, no calculation needed
-        if count == 2 {
-            return 0;
+        // If count of values of cards on table >= 2, that means we already a pair, no outs needed
+        if count >= 2 {
+            return Vec::new();
         }
     }
-    // There are four suits for each card, so 3 outs for each card
-    3
+    // Any of the 3 cards left for each card in hand makes a pair
+    let hand_values: Vec<Value> = hand.cards().iter().map(|card| card.value).collect();
+    get_cards_with_values(deck, &hand_values)
 }
 
-fn get_two_pairs_outs(hand: &Hand, community: &Hand) -> i8 {
-    let (_, card_values) = count_suit_and_value_on_table(&hand, &community);
-    let mut one_pair_found = false;
-    let mut second_pair_found = false;
+pub fn get_two_pairs_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
+    let mut num_of_pairs = 0;
 
     for (_, &count) in card_values.iter() {
-        // If count of values of cards on table == 4 (full suits of a card value), that means we already have two pairs, return outs = 0, no calculation needed
+        // If count of values of cards on table == 4 (full suits of a card value), that means we already have two pairs, no outs needed
         if count == 4 {
-            return 0;
+            return Vec::new();
         }
 
         // When there is a pair already
         if count == 2 {
-            if one_pair_found == false {
-                one_pair_found = true;
-                continue;
-            }
-            if one_pair_found {
-                second_pair_found = true;
-                continue;
-            }
+            num_of_pairs += 1;
         }
     }
 
-    if one_pair_found && second_pair_found {
-        return 0;
+    if num_of_pairs >= 2 {
+        return Vec::new();
     }
 
-    // If we already have a pair, we just have to get another pair, 3 outs for each card left
-    if one_pair_found {
-        return 3;
-    }
-    // 3 outs for each card, need two cards for a pair
-    hand.len() as i8 * 3
+    // Whether we already have a pair or not, we have to pair another card in hand, 3 outs for each card left
+    get_cards_with_values(deck, &get_unpaired_hand_values(hand, &card_values))
 }
 
-fn get_three_of_a_kind_outs(hand: &Hand, community: &Hand) -> i8 {
-    let (_, card_values) = count_suit_and_value_on_table(&hand, &community);
+pub fn get_three_of_a_kind_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
+    let mut paired_values = Vec::new();
 
-    for (_, &count) in card_values.iter() {
-        // If count of values of cards on table == 3, that means we already have a set, return outs = 0, no calculation needed
+    for (&value, &count) in card_values.iter() {
+        // If count of values of cards on table >= 3, that means we already have a set, no outs needed
         if count >= 3 {
-            return 0;
+            return Vec::new();
         }
 
-        // If we already have a pair, we just need one more card, 2 outs
-        if count == 2 {
-            return 2;
+        // If a card in hand is already paired, we just need one more card, 2 outs
+        if count == 2 && hand.cards().iter().any(|card| card.value == value) {
+            paired_values.push(value);
         }
     }
-    // If we have no pair in current hand, then we need at least two more cards
-    3
+
+    if !paired_values.is_empty() {
+        return get_cards_with_values(deck, &paired_values);
+    }
+    // If we have no pair in current hand, then we need at least two more cards of a value in hand
+    let hand_values: Vec<Value> = hand.cards().iter().map(|card| card.value).collect();
+    get_cards_with_values(deck, &hand_values)
 }
 
-fn get_straight_outs(hand: &Hand, community: &Hand) -> i8 {
-    let (_, card_values) = count_suit_and_value_on_table(&hand, &community);
-    let mut value_vector = Vec::new();
+// Find which kind of straight draw we have and the values of the cards that complete a straight
+pub fn get_straight_draw(hand: &Hand, community: &Hand) -> (StraightDraw, Vec<Value>) {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
+    let mut values: Vec<Value> = card_values.keys().copied().collect();
 
-    for (&value, _) in card_values.iter() {
-        value_vector.push(value as i8);
+    // If we already have a straight, no draw needed
+    if find_straight_high_value(&values).is_some() {
+        return (StraightDraw::Made, Vec::new());
     }
 
-    value_vector.sort();
-    let mut num_in_sequence = 1;
-
-    for (i, each_value) in value_vector.iter().enumerate() {
-        if i == 0 {
+    // Any value that makes five in a row, with the ace high or low, completes a straight
+    let mut straight_values = Vec::new();
+    let mut straight_high_values = Vec::new();
+    for value in Value::values() {
+        if card_values.contains_key(&value) {
             continue;
         }
-        if each_value - 1 == value_vector[i - 1] {
-            num_in_sequence += 1
+        values.push(value);
+        if let Some(high) = find_straight_high_value(&values) {
+            straight_values.push(value);
+            straight_high_values.push(high);
         }
+        values.pop();
     }
 
-    // If there are 3 community cards and the num of consecutive card is not 3, then there is no chance for straight
-    if community.len() as i8 == 3 && num_in_sequence < 3 {
-        return -1;
-    }
+    let draw = classify_straight_draw(&straight_values, &straight_high_values);
 
-    // If there are 3 community cards and the num of consecutive card is not 3, then there is no chance for straight
-    if community.len() as i8 == 4 && num_in_sequence < 4 {
-        return -1;
+    (draw, straight_values)
+}
+
+// Name the draw from the values that complete a straight and the high card of each of those straights
+pub(crate) fn classify_straight_draw(
+    straight_values: &[Value],
+    straight_high_values: &[Value],
+) -> StraightDraw {
+    match straight_values.len() {
+        0 => StraightDraw::NoDraw,
+        1 if straight_high_values[0] == Value::Five => StraightDraw::Wheel,
+        1 if straight_high_values[0] == Value::Ace => StraightDraw::Broadway,
+        1 => StraightDraw::Gutshot,
+        2 => {
+            // Open ended when the two straights are one apart, which means four in a row on table.
+            // The four in a row can also be between the two values when one of them makes a higher
+            // straight, e.g. the 8 of 4-5-6-7-9 makes 5 to 9.
+            let low_high = straight_high_values[0] as u8;
+            let high_high = straight_high_values[1] as u8;
+            let low_value = straight_values[0] as u8;
+            let high_value = straight_values[1] as u8;
+            if low_high.abs_diff(high_high) == 1 || low_value.abs_diff(high_value) == 5 {
+                StraightDraw::OpenEnded
+            } else {
+                StraightDraw::DoubleGutshot
+            }
+        }
+        // Only possible with more than 2 hole cards, e.g. in Omaha
+        _ => StraightDraw::Wrap,
     }
+}
 
-    // We only need 5 cards to have straight, but from 4 suits
-    (5 - num_in_sequence) * 4
+pub fn get_straight_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, straight_values) = get_straight_draw(hand, community);
+    get_cards_with_values(deck, &straight_values)
 }
 
-fn get_flush_outs(deck: &Deck, hand: &Hand, community: &Hand) -> i8 {
-    let (card_suits, _) = count_suit_and_value_on_table(&hand, &community);
+pub fn get_flush_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (card_suits, _) = count_suit_and_value_on_table(hand, community);
 
-    let mut num_of_highest_suit_outs: i8 = -1;
-    let mut outs: i8;
+    let mut flush_outs: Vec<Card> = Vec::new();
+    let mut outs: Vec<Card>;
 
     for (&suit, &count) in card_suits.iter() {
+        // If there are already 5 cards with the same suit, we have a flush, no outs needed
+        if count >= 5 {
+            return Vec::new();
+        }
+
         // When there is more than or equal to 4 community cards, but the count of suits is less then 4, skip the suit
         if community.len() >= 4 && count < 4 {
             continue;
@@ -183,72 +396,288 @@ fn get_flush_outs(deck: &Deck, hand: &Hand, community: &Hand) -> i8 {
             continue;
         }
 
-        outs = 0;
-        // For the remaining suits, get the number of cards with that suit
-        for card in deck.iter() {
-            if card.suit == suit {
-                outs += 1;
-            }
-        }
-        if outs > num_of_highest_suit_outs {
-            num_of_highest_suit_outs = outs
+        // For the remaining suits, get the cards with that suit
+        outs = deck
+            .iter()
+            .filter(|card| card.suit == suit)
+            .copied()
+            .collect();
+        if outs.len() > flush_outs.len() {
+            flush_outs = outs
         }
     }
 
-    num_of_highest_suit_outs
+    flush_outs.sort_by_key(|card| card.value);
+    flush_outs
 }
 
-fn get_full_house_outs(hand: &Hand, community: &Hand) -> i8 {
-    let (_, card_values) = count_suit_and_value_on_table(&hand, &community);
-    let mut one_pair_found = false;
-    let mut second_pair_found = false;
+pub fn get_full_house_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
+    let mut paired_values = Vec::new();
     let mut set_found = false;
 
-    for (_, &count) in card_values.iter() {
-        // If count of values of cards on table == 4 (full suits of a card value), that means we already have two pairs, only need 1 more card (2 outs) for full house
+    for (&value, &count) in card_values.iter() {
+        // If there is four of a kind, that already beats a full house, no outs needed
         if count == 4 {
-            return 2 * 2;
+            return Vec::new();
         }
 
-        // If we already one pair
         if count == 2 {
-            if set_found {
-                return 0;
-            }
-            if one_pair_found == false {
-                one_pair_found = true;
-                continue;
-            }
-            if one_pair_found {
-                second_pair_found = true;
-                continue;
-            }
+            paired_values.push(value);
         }
 
         if count == 3 {
-            if one_pair_found {
-                return 0;
+            // Two sets already make a full house
+            if set_found {
+                return Vec::new();
             }
             set_found = true;
         }
     }
 
-    if one_pair_found && second_pair_found {
-        return 2 * 2;
+    // If we already have a set and a pair, no outs needed
+    if set_found && !paired_values.is_empty() {
+        return Vec::new();
+    }
+
+    // If we already have a set, we need to pair any other card on table, 3 outs for each card
+    if set_found {
+        let single_values: Vec<Value> = card_values
+            .iter()
+            .filter(|(_, &count)| count == 1)
+            .map(|(&value, _)| value)
+            .collect();
+        return get_cards_with_values(deck, &single_values);
+    }
+
+    // When there are two pairs already, 2 outs for each pair
+    if paired_values.len() >= 2 {
+        return get_cards_with_values(deck, &paired_values);
     }
 
     // If we already have a pair, we have to get both another pair + 1 other card of the same value with the pair we have or 3 other cards of the same value
-    if one_pair_found {
-        // Two possibilities but we go with the one with higher outs:
-        // 3 outs for a set
-        // 3 outs for a pair + 2 outs for 1 other card
-        return 2 + 3;
+    // 2 outs for a set
+    // 3 outs for each other card in hand to make two pairs
+    // If none found, 3 outs for each card in hand to make a pair first
+    let mut values = paired_values;
+    values.extend(get_unpaired_hand_values(hand, &card_values));
+    get_cards_with_values(deck, &values)
+}
+
+// High card needs no outs, every hand has one
+pub fn get_high_card_outs() -> Vec<Card> {
+    Vec::new()
+}
+
+pub fn get_four_of_a_kind_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    let (_, card_values) = count_suit_and_value_on_table(hand, community);
+    let mut paired_values = Vec::new();
+
+    for (_, &count) in card_values.iter() {
+        // If count of values of cards on table == 4, that means we already have quads, no outs needed
+        if count == 4 {
+            return Vec::new();
+        }
+    }
+
+    for (&value, &count) in card_values.iter() {
+        // If we already have a set, there is only 1 card of that value left
+        if count == 3 {
+            return get_cards_with_values(deck, &[value]);
+        }
+
+        if count == 2 {
+            paired_values.push(value);
+        }
+    }
+
+    // If we only have pairs, we need the 2 cards left of that value
+    get_cards_with_values(deck, &paired_values)
+}
+
+// Given the values of cards with the same suit, find the highest card of a straight made with them, if any
+pub(crate) fn find_straight_high_value(values: &[Value]) -> Option<Value> {
+    let mut value_bits: u16 = 0;
+    for value in values {
+        value_bits |= 1 << (*value as u16);
+    }
+
+    // Going down from the ace, look for five values in a row
+    for high in (Value::Six as u16..=Value::Ace as u16).rev() {
+        let straight_bits = 0b11111 << (high - 4);
+        if value_bits & straight_bits == straight_bits {
+            return Some(Value::from_u8(high as u8));
+        }
     }
-    // If none found
-    3 + 3
+
+    // The ace can also be used as the low card of the wheel: A-2-3-4-5
+    let wheel_bits = 0b1111 | 1 << (Value::Ace as u16);
+    if value_bits & wheel_bits == wheel_bits {
+        return Some(Value::Five);
+    }
+    None
 }
 
+// Get the values of the cards on table for a given suit
+fn get_suited_values(hand: &Hand, community: &Hand, suit: Suit) -> Vec<Value> {
+    hand.cards()
+        .iter()
+        .chain(community.cards().iter())
+        .filter(|card| card.suit == suit)
+        .map(|card| card.value)
+        .collect()
+}
+
+// Get the cards in the deck that complete a straight flush with the cards on table.
+// When royal is true, only straight flushes to the ace are kept, otherwise only the lower ones.
+fn get_straight_flush_cards(deck: &Deck, hand: &Hand, community: &Hand, royal: bool) -> Vec<Card> {
+    let mut outs: Vec<Card> = Vec::new();
+    let mut suited_values: Vec<Value>;
+
+    for suit in Suit::suits() {
+        suited_values = get_suited_values(hand, community, suit);
+
+        // If there is already a straight flush of the kind we look for in this suit, no outs needed
+        if let Some(high) = find_straight_high_value(&suited_values) {
+            if (high == Value::Ace) == royal {
+                return Vec::new();
+            }
+        }
+
+        for card in deck.iter() {
+            if card.suit != suit {
+                continue;
+            }
+            suited_values.push(card.value);
+            if let Some(high) = find_straight_high_value(&suited_values) {
+                if (high == Value::Ace) == royal {
+                    outs.push(*card);
+                }
+            }
+            suited_values.pop();
+        }
+    }
+
+    outs.sort_by_key(|card| (card.value, card.suit.to_char()));
+    outs
+}
+
+pub fn get_straight_flush_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    get_straight_flush_cards(deck, hand, community, false)
+}
+
+pub fn get_royal_flush_outs(deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
+    get_straight_flush_cards(deck, hand, community, true)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StraightDraw {
+    /// There is already a straight
+    Made,
+    /// Four in a row that can be completed at both ends
+    OpenEnded,
+    /// Two different inside cards complete a straight
+    DoubleGutshot,
+    /// Three or more values complete a straight, with more than 2 hole cards
+    Wrap,
+    /// One inside card completes a straight
+    Gutshot,
+    /// Only one value completes the wheel: A-2-3-4-5
+    Wheel,
+    /// Only one value completes broadway: T-J-Q-K-A
+    Broadway,
+    /// No single card completes a straight
+    NoDraw,
+}
+
+impl fmt::Display for StraightDraw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Made => "Made straight",
+            Self::OpenEnded => "Open-ended straight draw",
+            Self::DoubleGutshot => "Double gutshot straight draw",
+            Self::Wrap => "Wrap straight draw",
+            Self::Gutshot => "Gutshot straight draw",
+            Self::Wheel => "Wheel draw",
+            Self::Broadway => "Broadway draw",
+            Self::NoDraw => "No straight draw",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Betting round, given by the number of community cards, or by the number of our cards in stud.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Street {
+    /// No community cards yet.
+    Preflop,
+    /// 3 community cards.
+    Flop,
+    /// 4 community cards.
+    Turn,
+    /// All 5 community cards, nothing left to come.
+    River,
+    /// Stud: 2 down cards and 1 up card.
+    ThirdStreet,
+    /// Stud: 4 cards.
+    FourthStreet,
+    /// Stud: 5 cards.
+    FifthStreet,
+    /// Stud: 6 cards.
+    SixthStreet,
+    /// Stud: all 7 cards, nothing left to come.
+    SeventhStreet,
+    /// Draw: our 5 cards before throwing any away.
+    Draw,
+}
+
+impl fmt::Display for Street {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Street::Preflop => "Preflop",
+            Street::Flop => "Flop",
+            Street::Turn => "Turn",
+            Street::River => "River",
+            Street::ThirdStreet => "3rd street",
+            Street::FourthStreet => "4th street",
+            Street::FifthStreet => "5th street",
+            Street::SixthStreet => "6th street",
+            Street::SeventhStreet => "7th street",
+            Street::Draw => "Before the draw",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Street {
+    // Function to find the street from the number of community cards, any other number of cards can't be on the table
+    pub fn from_num_community_cards(num_community_cards: usize) -> Result<Street, PokerProbError> {
+        match num_community_cards {
+            0 => Ok(Street::Preflop),
+            3 => Ok(Street::Flop),
+            4 => Ok(Street::Turn),
+            5 => Ok(Street::River),
+            _ => Err(PokerProbError::InvalidBoardSize(num_community_cards)),
+        }
+    }
+
+    // The out functions need cards on table and a card to come, so outs are only found
+    // on the flop and the turn, or from 3rd to 6th street in stud. Draw has discard options instead.
+    pub fn has_outs(self) -> bool {
+        !matches!(
+            self,
+            Street::Preflop | Street::River | Street::SeventhStreet | Street::Draw
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HandRank {
+    /// No matches, only the highest card counts.
+    HighCard,
     /// One Card matches another.
     OnePair,
     /// Two different pair of matching cards.
@@ -261,21 +690,102 @@ pub enum HandRank {
     Flush,
     /// Three of one value and two of another value
     FullHouse,
+    /// Four of the same value.
+    FourOfAKind,
+    /// Five cards in a sequence all of the same suit
+    StraightFlush,
+    /// Ten to ace all of the same suit
+    RoyalFlush,
+}
+
+impl fmt::Display for HandRank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::HighCard => "High Card",
+            Self::OnePair => "One Pair",
+            Self::TwoPair => "Two Pair",
+            Self::ThreeOfAKind => "Three Of A Kind",
+            Self::Straight => "Straight",
+            Self::Flush => "Flush",
+            Self::FullHouse => "Full House",
+            Self::FourOfAKind => "Four Of A Kind",
+            Self::StraightFlush => "Straight Flush",
+            Self::RoyalFlush => "Royal Flush",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for HandRank {
+    type Err = String;
+
+    // Function to read a hand rank written in snake case like in the JSON report, e.g. `three_of_a_kind`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(name.to_string()))
+            .map_err(|_| format!("'{}' is not a hand rank, e.g. three_of_a_kind", name))
+    }
 }
 
 impl HandRank {
-    pub fn calc_outs(self, deck: &Deck, hand: &Hand, community: &Hand) -> i8 {
+    /// Every hand rank, from high card to royal flush.
+    pub fn all() -> [HandRank; 10] {
+        [
+            HandRank::HighCard,
+            HandRank::OnePair,
+            HandRank::TwoPair,
+            HandRank::ThreeOfAKind,
+            HandRank::Straight,
+            HandRank::Flush,
+            HandRank::FullHouse,
+            HandRank::FourOfAKind,
+            HandRank::StraightFlush,
+            HandRank::RoyalFlush,
+        ]
+    }
+
+    /// Map a rank from rs_poker to the matching hand rank.
+    pub fn from_rank(rank: &Rank) -> HandRank {
+        match rank {
+            Rank::HighCard(_) => Self::HighCard,
+            Rank::OnePair(_) => Self::OnePair,
+            Rank::TwoPair(_) => Self::TwoPair,
+            Rank::ThreeOfAKind(_) => Self::ThreeOfAKind,
+            Rank::Straight(_) => Self::Straight,
+            Rank::Flush(_) => Self::Flush,
+            Rank::FullHouse(_) => Self::FullHouse,
+            Rank::FourOfAKind(_) => Self::FourOfAKind,
+            // rs_poker ranks a straight flush by its position, 9 is the one to the ace
+            Rank::StraightFlush(9) => Self::RoyalFlush,
+            Rank::StraightFlush(_) => Self::StraightFlush,
+        }
+    }
+
+    pub fn calc_outs(self, deck: &Deck, hand: &Hand, community: &Hand) -> Vec<Card> {
         match self {
-            Self::OnePair => get_one_pair_outs(hand, community),
-            Self::TwoPair => get_two_pairs_outs(hand, community),
-            Self::ThreeOfAKind => get_three_of_a_kind_outs(hand, community),
-            Self::Straight => get_straight_outs(hand, community),
+            Self::HighCard => get_high_card_outs(),
+            Self::OnePair => get_one_pair_outs(deck, hand, community),
+            Self::TwoPair => get_two_pairs_outs(deck, hand, community),
+            Self::ThreeOfAKind => get_three_of_a_kind_outs(deck, hand, community),
+            Self::Straight => get_straight_outs(deck, hand, community),
             Self::Flush => get_flush_outs(deck, hand, community),
-            Self::FullHouse => get_full_house_outs(hand, community),
+            Self::FullHouse => get_full_house_outs(deck, hand, community),
+            Self::FourOfAKind => get_four_of_a_kind_outs(deck, hand, community),
+            Self::StraightFlush => get_straight_flush_outs(deck, hand, community),
+            Self::RoyalFlush => get_royal_flush_outs(deck, hand, community),
         }
     }
 }
 
+/// Best hand made with the cards on table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MadeHand {
+    pub hand_rank: HandRank,
+    /// Best 5 cards, the ones making the hand rank first, fewer while we don't hold 5 cards.
+    pub cards: Vec<Card>,
+    /// Cards of the best 5 that only break ties, e.g. the 3 cards next to one pair.
+    pub kickers: Vec<Card>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,18 +810,170 @@ mod tests {
         assert_eq!(calc_4_and_2_probs(false, 4, -1), 0);
     }
 
+    // 26 outs is already over 100% with the rule of 4
+    #[test]
+    fn test_calc_4_and_2_probs_capped() {
+        assert_eq!(calc_4_and_2_probs(true, 4, 26), 100);
+        assert_eq!(calc_4_and_2_probs(true, 4, 33), 100);
+        assert_eq!(calc_4_and_2_probs(false, 3, 51), 100);
+    }
+
+    // Stud on 4th street, all in: 3 cards to come, capped at 100%
+    #[test]
+    fn test_calc_rule_of_2_probs() {
+        assert_eq!(calc_rule_of_2_probs(3, 8), 48);
+        assert_eq!(calc_rule_of_2_probs(1, 8), 16);
+        assert_eq!(calc_rule_of_2_probs(4, 40), 100);
+        assert_eq!(calc_rule_of_2_probs(3, -1), 0);
+    }
+
+    // Flush draw with 9 outs on the flop, all in: the rule of 4 says 36%, the exact answer is 1 - C(38, 2) / C(47, 2)
+    #[test]
+    fn test_exact_probs_two_cards() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let probs = calc_exact_probs(Game::Holdem, true, &deck, &hand, &community);
+        assert!((probs[&HandRank::Flush] - (1.0 - 703.0 / 1081.0) * 100.0).abs() < 1e-9);
+    }
+
+    // Only the turn is dealt when not all in, so the flush probability is 9 outs out of 47 cards
+    #[test]
+    fn test_exact_probs_one_card() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let probs = calc_exact_probs(Game::Holdem, false, &deck, &hand, &community);
+        assert!((probs[&HandRank::Flush] - 9.0 * 100.0 / 47.0).abs() < 1e-9);
+    }
+
+    // Nothing is left to come on the river, the made hand has a probability of 100%
+    #[test]
+    fn test_exact_probs_river() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let community = Hand::new_from_str("7h6c2cAs3d").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let probs = calc_exact_probs(Game::Holdem, true, &deck, &hand, &community);
+        assert_eq!(probs[&HandRank::ThreeOfAKind], 100.0);
+        assert_eq!(probs.len(), 1);
+    }
+
+    // Preflop the flop is dealt, a pocket pair flops quads when both other cards come, 48 of C(50, 3) flops
+    #[test]
+    fn test_exact_probs_preflop() {
+        let hand = Hand::new_from_str("7h7d").unwrap();
+        let community = Hand::new_from_str("").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let probs = calc_exact_probs(Game::Holdem, false, &deck, &hand, &community);
+        assert!((probs[&HandRank::FourOfAKind] - 48.0 * 100.0 / 19600.0).abs() < 1e-9);
+        assert!(!probs.contains_key(&HandRank::HighCard));
+    }
+
+    #[test]
+    fn test_street_from_num_community_cards() {
+        assert_eq!(Street::from_num_community_cards(0), Ok(Street::Preflop));
+        assert_eq!(Street::from_num_community_cards(3), Ok(Street::Flop));
+        assert_eq!(Street::from_num_community_cards(4), Ok(Street::Turn));
+        assert_eq!(Street::from_num_community_cards(5), Ok(Street::River));
+    }
+
+    #[test]
+    fn test_street_invalid_num_community_cards() {
+        assert!(Street::from_num_community_cards(1).is_err());
+        assert!(Street::from_num_community_cards(2).is_err());
+        assert_eq!(
+            Street::from_num_community_cards(6),
+            Err(PokerProbError::InvalidBoardSize(6))
+        );
+    }
+
+    #[test]
+    fn test_parse_cards() {
+        let hand = parse_cards("Ah kd").unwrap();
+        assert_eq!(
+            hand.cards(),
+            &[
+                Card::new(Value::Ace, Suit::Heart),
+                Card::new(Value::King, Suit::Diamond)
+            ]
+        );
+        assert!(parse_cards("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_cards() {
+        assert_eq!(
+            parse_cards("Ah1d"),
+            Err(PokerProbError::InvalidCard("1d".to_string()))
+        );
+        assert_eq!(
+            parse_cards("AhK"),
+            Err(PokerProbError::InvalidCard("K".to_string()))
+        );
+        assert_eq!(
+            parse_cards("AhAh"),
+            Err(PokerProbError::DuplicateCard(Card::new(
+                Value::Ace,
+                Suit::Heart
+            )))
+        );
+    }
+
+    // A card in both our hand and on the board is reported instead of being ignored
+    #[test]
+    fn test_duplicate_unknown_cards() {
+        let hand = Hand::new_from_str("AdKh").unwrap();
+        let community = Hand::new_from_str("Jd8cAd").unwrap();
+        assert_eq!(
+            get_unknown_cards(Game::Holdem, &hand, &community, &[]).err(),
+            Some(PokerProbError::DuplicateCard(Card::new(
+                Value::Ace,
+                Suit::Diamond
+            )))
+        );
+    }
+
     #[test]
     fn test_correct_num_of_unknown_cards() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8c3d").unwrap();
-        assert_eq!(get_unknown_cards(&hand, &community).len(), 47);
+        assert_eq!(
+            get_unknown_cards(Game::Holdem, &hand, &community, &[])
+                .unwrap()
+                .len(),
+            47
+        );
     }
 
     #[test]
     fn test_incorrect_num_of_unknown_cards() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8c3d4s").unwrap();
-        assert_ne!(get_unknown_cards(&hand, &community).len(), 47);
+        assert_ne!(
+            get_unknown_cards(Game::Holdem, &hand, &community, &[])
+                .unwrap()
+                .len(),
+            47
+        );
+    }
+
+    // The upcards of the other players are removed with our cards
+    #[test]
+    fn test_dead_cards_not_in_unknown_cards() {
+        let hand = parse_cards("9h8h7h6c").unwrap();
+        let dead_cards = parse_cards("Th5h2h").unwrap();
+        let deck =
+            get_unknown_cards(Game::Stud, &hand, &Hand::default(), dead_cards.cards()).unwrap();
+        assert_eq!(deck.len(), 45);
+        assert!(!deck.contains(&Card::new(Value::Ten, Suit::Heart)));
+
+        assert_eq!(
+            get_unknown_cards(Game::Stud, &hand, &Hand::default(), &[hand.cards()[0]]).err(),
+            Some(PokerProbError::DuplicateCard(Card::new(
+                Value::Nine,
+                Suit::Heart
+            )))
+        );
     }
 
     // Testing if we have the correct number of count of suits and values on a hand
@@ -338,14 +1000,16 @@ mod tests {
     fn test_existing_one_pair_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Ah8c3s4s").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_one_pair_outs(&deck, &hand, &community).len(), 0);
     }
 
     #[test]
     fn test_existing_one_pair_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("2h8cks4s").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_one_pair_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Testing when we have at least one pair, outs should 0, pair at the middle
@@ -353,7 +1017,8 @@ mod tests {
     fn test_one_pair_outs_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("7h8cks4s").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_one_pair_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Testing when we have at least one pair, outs should 0, pair at the bottom
@@ -361,7 +1026,8 @@ mod tests {
     fn test_one_pair_outs_3() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("7h8cks").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_one_pair_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Testing when we have at least one pair, outs should 0, pair at the bottom
@@ -369,15 +1035,21 @@ mod tests {
     fn test_one_pair_outs_4() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("7h8cAs").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_one_pair_outs(&deck, &hand, &community).len(), 0);
     }
 
-    // Testing when we have no pair
+    // Testing when we have no pair, the 3 aces and 3 kings left are the outs
     #[test]
     fn test_one_pair_outs_5() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("7h8c2s").unwrap();
-        assert_eq!(get_one_pair_outs(&hand, &community), 3);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let outs = get_one_pair_outs(&deck, &hand, &community);
+        assert_eq!(outs.len(), 6);
+        assert!(outs
+            .iter()
+            .all(|card| card.value == Value::Ace || card.value == Value::King));
     }
 
     // Testing when we have two pairs already
@@ -385,14 +1057,16 @@ mod tests {
     fn test_existing_two_pairs_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("AsKd2s").unwrap();
-        assert_eq!(get_two_pairs_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_two_pairs_outs(&deck, &hand, &community).len(), 0);
     }
 
     #[test]
     fn test_existing_two_pairs_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("As4cKd2s").unwrap();
-        assert_eq!(get_two_pairs_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_two_pairs_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Test when we already have one pair
@@ -400,7 +1074,8 @@ mod tests {
     fn test_one_in_two_pairs_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("As4c5d2s").unwrap();
-        assert_eq!(get_two_pairs_outs(&hand, &community), 3);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_two_pairs_outs(&deck, &hand, &community).len(), 3);
     }
 
     // Test when we already have one pair
@@ -408,7 +1083,8 @@ mod tests {
     fn test_one_in_two_pairs_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("3skd4c").unwrap();
-        assert_eq!(get_two_pairs_outs(&hand, &community), 3);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_two_pairs_outs(&deck, &hand, &community).len(), 3);
     }
 
     // Test when we have no pair
@@ -416,7 +1092,8 @@ mod tests {
     fn test_two_pairs_outs_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("3s4cqd2s").unwrap();
-        assert_eq!(get_two_pairs_outs(&hand, &community), 6);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_two_pairs_outs(&deck, &hand, &community).len(), 6);
     }
 
     // Test when we have already have three of a kind
@@ -424,7 +1101,8 @@ mod tests {
     fn test_existing_three_of_a_kind_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("As4cqdAc").unwrap();
-        assert_eq!(get_three_of_a_kind_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_three_of_a_kind_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Test when we have already have three of a kind
@@ -432,7 +1110,8 @@ mod tests {
     fn test_existing_three_of_a_kind_2() {
         let hand = Hand::new_from_str("AdAh").unwrap();
         let community = Hand::new_from_str("As4cqdAc").unwrap();
-        assert_eq!(get_three_of_a_kind_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_three_of_a_kind_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Test when we have already have a pair
@@ -440,7 +1119,8 @@ mod tests {
     fn test_three_of_a_kind_outs_1() {
         let hand = Hand::new_from_str("AdAh").unwrap();
         let community = Hand::new_from_str("2s4cqd4h").unwrap();
-        assert_eq!(get_three_of_a_kind_outs(&hand, &community), 2);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_three_of_a_kind_outs(&deck, &hand, &community).len(), 2);
     }
 
     // Testing when we have no chance of having straight
@@ -448,35 +1128,46 @@ mod tests {
     fn test_impossible_straight_1() {
         let hand = Hand::new_from_str("7dkh").unwrap();
         let community = Hand::new_from_str("Jd2c3s").unwrap();
-        assert_eq!(get_straight_outs(&hand, &community), -1);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_outs(&deck, &hand, &community).len(), 0);
     }
 
     #[test]
     fn test_impossible_straight_2() {
         let hand = Hand::new_from_str("7dkh").unwrap();
         let community = Hand::new_from_str("Jd2c3s4s").unwrap();
-        assert_eq!(get_straight_outs(&hand, &community), -1);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_outs(&deck, &hand, &community).len(), 0);
     }
 
+    // Both the 6 (3 to 7) and the ace (wheel) complete the straight
     #[test]
     fn test_straight_outs_1() {
         let hand = Hand::new_from_str("7d3s").unwrap();
         let community = Hand::new_from_str("Jd2s4s5d").unwrap();
-        assert_eq!(get_straight_outs(&hand, &community), 4);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let outs = get_straight_outs(&deck, &hand, &community);
+        assert_eq!(outs.len(), 8);
+        assert!(outs
+            .iter()
+            .all(|card| card.value == Value::Six || card.value == Value::Ace));
     }
 
+    // No single card completes 2-3-4-7, a straight needs two more cards
     #[test]
     fn test_straight_outs_2() {
         let hand = Hand::new_from_str("7d3s").unwrap();
         let community = Hand::new_from_str("Jd2s4s").unwrap();
-        assert_eq!(get_straight_outs(&hand, &community), 8);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_outs(&deck, &hand, &community).len(), 0);
     }
 
     #[test]
     fn test_straight_outs_3() {
         let hand = Hand::new_from_str("7d3s").unwrap();
         let community = Hand::new_from_str("3d2h4s").unwrap();
-        assert_eq!(get_straight_outs(&hand, &community), 8);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Testing when we have 4 community cards already, but number of cards of same suits is less than 4
@@ -484,8 +1175,8 @@ mod tests {
     fn test_impossible_flush_outs_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8c3s4s").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), -1);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 0);
     }
 
     // Testing when we have the flops (3 community cards), but number of cards of same suits is less than 3
@@ -493,8 +1184,8 @@ mod tests {
     fn test_impossible_flush_outs_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8c3s").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), -1);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 0);
     }
 
     // In this case, we have 3 diamond suited cards out of 13 diamond suited cards, the correct number should be 10
@@ -502,8 +1193,8 @@ mod tests {
     fn test_correct_flush_outs_1() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8c3d").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), 10);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 10);
     }
 
     // In this case, we have 4 diamond suited cards out of 13 diamond suited cards, the correct number should be 9
@@ -511,8 +1202,8 @@ mod tests {
     fn test_correct_flush_outs_2() {
         let hand = Hand::new_from_str("Adkh").unwrap();
         let community = Hand::new_from_str("Jd8d3d").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), 9);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 9);
     }
 
     // In this case, we have two card with the same value in hand
@@ -520,8 +1211,8 @@ mod tests {
     fn test_correct_flush_outs_3() {
         let hand = Hand::new_from_str("AdAh").unwrap();
         let community = Hand::new_from_str("Jd8d3d").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), 9);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 9);
     }
 
     // In this case, we have two card with the same value
@@ -529,8 +1220,8 @@ mod tests {
     fn test_correct_flush_outs_4() {
         let hand = Hand::new_from_str("Ad3d").unwrap();
         let community = Hand::new_from_str("Jd8dAh").unwrap();
-        let deck: Deck = get_unknown_cards(&hand, &community);
-        assert_eq!(get_flush_outs(&deck, &hand, &community), 9);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_flush_outs(&deck, &hand, &community).len(), 9);
     }
 
     // In this case, we have a pair and a set already
@@ -538,14 +1229,16 @@ mod tests {
     fn test_existing_full_house_1() {
         let hand = Hand::new_from_str("Ad3d").unwrap();
         let community = Hand::new_from_str("3s3hAh").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 0);
     }
 
     #[test]
     fn test_existing_full_house_2() {
         let hand = Hand::new_from_str("AdAh").unwrap();
         let community = Hand::new_from_str("3s3h4h3c").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 0);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 0);
     }
 
     // When there are two pairs already
@@ -553,7 +1246,8 @@ mod tests {
     fn test_full_house_outs_1() {
         let hand = Hand::new_from_str("AdAh").unwrap();
         let community = Hand::new_from_str("4h3c3h").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 4);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 4);
     }
 
     // When there are two pairs already
@@ -561,7 +1255,8 @@ mod tests {
     fn test_full_house_outs_2() {
         let hand = Hand::new_from_str("Ad3h").unwrap();
         let community = Hand::new_from_str("4h3c5cAh").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 4);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 4);
     }
 
     // When there is one pair already
@@ -569,7 +1264,8 @@ mod tests {
     fn test_full_house_outs_3() {
         let hand = Hand::new_from_str("Ad3h").unwrap();
         let community = Hand::new_from_str("4h3c5c6h").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 5);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 5);
     }
 
     // When there is one pair already
@@ -577,7 +1273,8 @@ mod tests {
     fn test_full_house_outs_4() {
         let hand = Hand::new_from_str("4sAd").unwrap();
         let community = Hand::new_from_str("4h3c5c").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 5);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 5);
     }
 
     // When there is nothing
@@ -585,6 +1282,324 @@ mod tests {
     fn test_full_house_outs_5() {
         let hand = Hand::new_from_str("4sAd").unwrap();
         let community = Hand::new_from_str("6h3c5c").unwrap();
-        assert_eq!(get_full_house_outs(&hand, &community), 6);
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_full_house_outs(&deck, &hand, &community).len(), 6);
+    }
+
+    #[test]
+    fn test_high_card_outs() {
+        assert_eq!(get_high_card_outs().len(), 0);
+    }
+
+    // In this case, we have four of a kind already
+    #[test]
+    fn test_existing_four_of_a_kind_1() {
+        let hand = Hand::new_from_str("AdAh").unwrap();
+        let community = Hand::new_from_str("AsAc4h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_four_of_a_kind_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    // When there is a set already, only one card is left
+    #[test]
+    fn test_four_of_a_kind_outs_1() {
+        let hand = Hand::new_from_str("AdAh").unwrap();
+        let community = Hand::new_from_str("As4c5h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_four_of_a_kind_outs(&deck, &hand, &community).len(), 1);
+    }
+
+    // When there is a set and a pair, the set is the only way to quads with one card
+    #[test]
+    fn test_four_of_a_kind_outs_2() {
+        let hand = Hand::new_from_str("Ad4h").unwrap();
+        let community = Hand::new_from_str("As4cAc").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_four_of_a_kind_outs(&deck, &hand, &community).len(), 1);
+    }
+
+    // When there is a pair already
+    #[test]
+    fn test_four_of_a_kind_outs_3() {
+        let hand = Hand::new_from_str("AdAh").unwrap();
+        let community = Hand::new_from_str("3s4c5h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_four_of_a_kind_outs(&deck, &hand, &community).len(), 2);
+    }
+
+    // When there is nothing
+    #[test]
+    fn test_four_of_a_kind_outs_4() {
+        let hand = Hand::new_from_str("Ad7h").unwrap();
+        let community = Hand::new_from_str("3s4c5h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_four_of_a_kind_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    #[test]
+    fn test_find_straight_high_value() {
+        let values = vec![
+            Value::Ace,
+            Value::Two,
+            Value::Three,
+            Value::Four,
+            Value::Five,
+        ];
+        assert_eq!(find_straight_high_value(&values), Some(Value::Five));
+        let values = vec![
+            Value::Ten,
+            Value::Jack,
+            Value::Queen,
+            Value::King,
+            Value::Ace,
+        ];
+        assert_eq!(find_straight_high_value(&values), Some(Value::Ace));
+        let values = vec![
+            Value::Nine,
+            Value::Jack,
+            Value::Queen,
+            Value::King,
+            Value::Ace,
+        ];
+        assert_eq!(find_straight_high_value(&values), None);
+    }
+
+    // In this case, we have a straight flush already
+    #[test]
+    fn test_existing_straight_flush_1() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h5h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_flush_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    // Open ended straight flush draw, 5h and Th complete it
+    #[test]
+    fn test_straight_flush_outs_1() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_flush_outs(&deck, &hand, &community).len(), 2);
+    }
+
+    // Straight flush draw to the wheel, only the 5h completes it
+    #[test]
+    fn test_straight_flush_outs_2() {
+        let hand = Hand::new_from_str("Ah2h").unwrap();
+        let community = Hand::new_from_str("3h4hKc").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_flush_outs(&deck, &hand, &community).len(), 1);
+    }
+
+    // The only card that completes this draw makes a royal flush instead
+    #[test]
+    fn test_straight_flush_outs_3() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("QhJh2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_straight_flush_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    #[test]
+    fn test_existing_royal_flush_1() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("QhJhTh").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_royal_flush_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    #[test]
+    fn test_royal_flush_outs_1() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("QhJh2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_royal_flush_outs(&deck, &hand, &community).len(), 1);
+    }
+
+    #[test]
+    fn test_impossible_royal_flush_1() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        assert_eq!(get_royal_flush_outs(&deck, &hand, &community).len(), 0);
+    }
+
+    #[test]
+    fn test_hand_rank_from_rank() {
+        let royal = Hand::new_from_str("AhKhQhJhTh").unwrap();
+        assert_eq!(HandRank::from_rank(&royal.rank()), HandRank::RoyalFlush);
+        let straight_flush = Hand::new_from_str("KhQhJhTh9h").unwrap();
+        assert_eq!(
+            HandRank::from_rank(&straight_flush.rank()),
+            HandRank::StraightFlush
+        );
+        let high_card = Hand::new_from_str("AhKd9c4s2h").unwrap();
+        assert_eq!(HandRank::from_rank(&high_card.rank()), HandRank::HighCard);
+    }
+
+    // 9-8-7-6 can be completed with a 5 or a ten
+    #[test]
+    fn test_open_ended_straight_draw() {
+        let hand = Hand::new_from_str("9h8d").unwrap();
+        let community = Hand::new_from_str("7c6s2h").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::OpenEnded, vec![Value::Five, Value::Ten])
+        );
+    }
+
+    // 4-5-6-7 with a 9 is still open-ended, the 8 makes 4 to 8 before 5 to 9
+    #[test]
+    fn test_open_ended_straight_draw_with_extra_card() {
+        let hand = Hand::new_from_str("4h5d").unwrap();
+        let community = Hand::new_from_str("6c7s9h").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::OpenEnded, vec![Value::Three, Value::Eight])
+        );
+    }
+
+    // 5-6-8-9 only needs the 7
+    #[test]
+    fn test_gutshot_straight_draw() {
+        let hand = Hand::new_from_str("5h6d").unwrap();
+        let community = Hand::new_from_str("8c9sKh").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::Gutshot, vec![Value::Seven])
+        );
+    }
+
+    // 5-7-8-9-J can be completed with a 6 or a ten
+    #[test]
+    fn test_double_gutshot_straight_draw() {
+        let hand = Hand::new_from_str("5h7d").unwrap();
+        let community = Hand::new_from_str("8c9sJh").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::DoubleGutshot, vec![Value::Six, Value::Ten])
+        );
+    }
+
+    // A-2-3-4 only needs the 5, the ace plays low
+    #[test]
+    fn test_wheel_straight_draw() {
+        let hand = Hand::new_from_str("Ah2d").unwrap();
+        let community = Hand::new_from_str("3c4sJh").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::Wheel, vec![Value::Five])
+        );
+    }
+
+    // A-K-Q-J only needs the ten
+    #[test]
+    fn test_broadway_straight_draw() {
+        let hand = Hand::new_from_str("AhKd").unwrap();
+        let community = Hand::new_from_str("QcJs2h").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::Broadway, vec![Value::Ten])
+        );
+    }
+
+    // 2-3-4-5 is open ended, the ace makes the wheel and the 6 makes 2 to 6
+    #[test]
+    fn test_open_ended_wheel_straight_draw() {
+        let hand = Hand::new_from_str("2h3d").unwrap();
+        let community = Hand::new_from_str("4c5sJh").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::OpenEnded, vec![Value::Six, Value::Ace])
+        );
+    }
+
+    #[test]
+    fn test_no_straight_draw() {
+        let hand = Hand::new_from_str("7dkh").unwrap();
+        let community = Hand::new_from_str("Jd2c3s").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::NoDraw, vec![])
+        );
+    }
+
+    #[test]
+    fn test_made_straight() {
+        let hand = Hand::new_from_str("Ah2d").unwrap();
+        let community = Hand::new_from_str("3c4s5h").unwrap();
+        assert_eq!(
+            get_straight_draw(&hand, &community),
+            (StraightDraw::Made, vec![])
+        );
+    }
+
+    // Flush draw with an open-ended straight draw, the straight flush cards and the flush cards that also make a straight are counted once
+    #[test]
+    fn test_combined_outs_1() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let combined_outs = calc_combined_outs(Game::Holdem, &deck, &hand, &community);
+        assert_eq!(combined_outs[&HandRank::StraightFlush].len(), 2);
+        assert_eq!(combined_outs[&HandRank::Flush].len(), 7);
+        assert_eq!(combined_outs[&HandRank::Straight].len(), 6);
+        // The cards pairing one of the cards on table make one pair
+        assert_eq!(combined_outs[&HandRank::OnePair].len(), 14);
+    }
+
+    // With a set already, only the cards that make a full house or quads are outs
+    #[test]
+    fn test_combined_outs_2() {
+        let hand = Hand::new_from_str("AdAh").unwrap();
+        let community = Hand::new_from_str("As4c5h").unwrap();
+        let deck: Deck = get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let combined_outs = calc_combined_outs(Game::Holdem, &deck, &hand, &community);
+        assert_eq!(combined_outs.len(), 2);
+        assert_eq!(combined_outs[&HandRank::FourOfAKind].len(), 1);
+        assert_eq!(combined_outs[&HandRank::FullHouse].len(), 6);
+    }
+
+    #[test]
+    fn test_current_hand_rank() {
+        let hand = Hand::new_from_str("AdAh").unwrap();
+        let community = Hand::new_from_str("As4c5h").unwrap();
+        assert_eq!(
+            get_current_hand_rank(Game::Holdem, &hand, &community),
+            HandRank::ThreeOfAKind
+        );
+    }
+
+    // The pair comes first, the other 3 cards are the kickers from the highest down
+    #[test]
+    fn test_made_hand_one_pair() {
+        let hand = parse_cards("AhKh").unwrap();
+        let community = parse_cards("7h6h2cAs3d").unwrap();
+        let made_hand = get_made_hand(Game::Holdem, &hand, &community);
+        assert_eq!(made_hand.hand_rank, HandRank::OnePair);
+        assert_eq!(made_hand.cards, parse_cards("AsAhKh7h6h").unwrap().cards());
+        assert_eq!(made_hand.kickers, parse_cards("Kh7h6h").unwrap().cards());
+    }
+
+    // The ace of the wheel goes last, a straight has no kickers
+    #[test]
+    fn test_made_hand_wheel() {
+        let hand = parse_cards("5h4d").unwrap();
+        let community = parse_cards("3c2sAdKd").unwrap();
+        let made_hand = get_made_hand(Game::Holdem, &hand, &community);
+        assert_eq!(made_hand.hand_rank, HandRank::Straight);
+        assert_eq!(made_hand.cards, parse_cards("5h4d3c2sAd").unwrap().cards());
+        assert!(made_hand.kickers.is_empty());
+    }
+
+    // Exactly 2 hole cards play in Omaha, the third ace in hand is not a kicker
+    #[test]
+    fn test_made_hand_omaha() {
+        let hand = parse_cards("AhAdAc2h").unwrap();
+        let community = parse_cards("Ks9c4d").unwrap();
+        let made_hand = get_made_hand(Game::Omaha, &hand, &community);
+        assert_eq!(made_hand.hand_rank, HandRank::OnePair);
+        assert_eq!(made_hand.cards.len(), 5);
+        assert_eq!(made_hand.kickers, parse_cards("Ks9c4d").unwrap().cards());
     }
 }
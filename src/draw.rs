@@ -0,0 +1,152 @@
+use crate::calc_prob::HandRank;
+use crate::game::Game;
+use rs_poker::core::{Card, CardIter, Deck, Hand, Rankable};
+use std::collections::BTreeMap;
+
+/// One way to play the draw, the cards thrown away are replaced from the unknown cards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscardOption {
+    pub keep: Vec<Card>,
+    pub discard: Vec<Card>,
+    /// Exact probability of finishing with each hand rank after the draw, in percent.
+    pub exact_probs: BTreeMap<HandRank, f64>,
+    /// Exact probability of finishing with the target hand rank or better, in percent.
+    pub target_prob: f64,
+}
+
+/// Every discard option of a draw hand, from the best chance of making the target down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawReport {
+    pub target: HandRank,
+    pub options: Vec<DiscardOption>,
+}
+
+// Function to pick the hand rank to aim for when none is given, the one right above the made hand
+pub fn default_target(game: Game, current_hand_rank: HandRank) -> HandRank {
+    let hand_ranks = game.hand_ranks();
+    let next = (game.strength(current_hand_rank) + 1).min(hand_ranks.len() - 1);
+    hand_ranks[next]
+}
+
+// Function to go through every new card for the discarded ones and calculate the exact
+// probability (in percent) of finishing with each hand rank
+fn calc_draw_probs(
+    game: Game,
+    unknown_cards: &[Card],
+    keep: &[Card],
+    num_cards_to_draw: usize,
+) -> BTreeMap<HandRank, f64> {
+    let mut rank_counts: BTreeMap<HandRank, u32> = BTreeMap::new();
+    let mut num_draws: u32 = 0;
+
+    if num_cards_to_draw == 0 {
+        rank_counts.insert(game.hand_rank(keep, &[]), 1);
+        num_draws = 1;
+    } else {
+        for draw in CardIter::new(unknown_cards, num_cards_to_draw) {
+            *rank_counts.entry(game.hand_rank(keep, &draw)).or_insert(0) += 1;
+            num_draws += 1;
+        }
+    }
+
+    rank_counts
+        .into_iter()
+        .map(|(hand_rank, count)| (hand_rank, count as f64 * 100.0 / num_draws as f64))
+        .collect()
+}
+
+// Function to try every set of cards to throw away, 32 options for 5 cards, and sort them by the
+// chance of finishing with the target hand rank or better
+pub fn calc_discard_options(
+    game: Game,
+    deck: &Deck,
+    hand: &Hand,
+    target: HandRank,
+) -> Vec<DiscardOption> {
+    // Sort the unknown cards so the enumeration does not depend on the hash order of the deck
+    let mut unknown_cards: Vec<Card> = deck.iter().copied().collect();
+    unknown_cards.sort();
+
+    let cards = hand.cards();
+    let target_strength = game.strength(target);
+
+    // Fewer discards first, the stable sort keeps them first when the chances are the same
+    let mut discard_masks: Vec<u32> = (0..1 << cards.len()).collect();
+    discard_masks.sort_by_key(|mask| mask.count_ones());
+
+    let mut options: Vec<DiscardOption> = discard_masks
+        .into_iter()
+        .map(|mask| {
+            let mut keep: Vec<Card> = Vec::new();
+            let mut discard: Vec<Card> = Vec::new();
+            for (i, &card) in cards.iter().enumerate() {
+                if mask & (1 << i) != 0 {
+                    discard.push(card);
+                } else {
+                    keep.push(card);
+                }
+            }
+
+            let exact_probs = calc_draw_probs(game, &unknown_cards, &keep, discard.len());
+            let target_prob = exact_probs
+                .iter()
+                .filter(|(&hand_rank, _)| game.strength(hand_rank) >= target_strength)
+                .map(|(_, prob)| prob)
+                .sum();
+            DiscardOption {
+                keep,
+                discard,
+                exact_probs,
+                target_prob,
+            }
+        })
+        .collect();
+
+    options.sort_by(|a, b| b.target_prob.total_cmp(&a.target_prob));
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_prob::{get_unknown_cards, parse_cards};
+    use rs_poker::core::{Suit, Value};
+
+    fn discard_options(hand: &str, target: HandRank) -> Vec<DiscardOption> {
+        let hand = parse_cards(hand).unwrap();
+        let deck = get_unknown_cards(Game::Draw, &hand, &Hand::default(), &[]).unwrap();
+        calc_discard_options(Game::Draw, &deck, &hand, target)
+    }
+
+    #[test]
+    fn test_default_target() {
+        assert_eq!(
+            default_target(Game::Draw, HandRank::OnePair),
+            HandRank::TwoPair
+        );
+        assert_eq!(
+            default_target(Game::Draw, HandRank::RoyalFlush),
+            HandRank::RoyalFlush
+        );
+    }
+
+    // Drawing one card to 4 hearts: 9 of the 47 unknown cards make the flush
+    #[test]
+    fn test_flush_draw() {
+        let options = discard_options("Ah9h7h4hKc", HandRank::Flush);
+        assert_eq!(options.len(), 32);
+        let best = &options[0];
+        assert_eq!(best.discard, vec![Card::new(Value::King, Suit::Club)]);
+        assert!((best.target_prob - 9.0 * 100.0 / 47.0).abs() < 1e-9);
+        let total: f64 = best.exact_probs.values().sum();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+
+    // A made straight is kept, nothing is drawn
+    #[test]
+    fn test_keep_made_hand() {
+        let options = discard_options("9h8c7d6s5h", HandRank::Straight);
+        assert!(options[0].discard.is_empty());
+        assert_eq!(options[0].target_prob, 100.0);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::calc_prob;
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::range::{Range, WeightedCombo};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rs_poker::core::{Card, Hand, Rankable};
+use serde::Serialize;
+
+/// Share of the simulated runouts that we win, tie or lose, in percent.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Equity {
+    pub win: f64,
+    pub tie: f64,
+    pub lose: f64,
+}
+
+// Function to deal out the rest of the board many times and count how often our hand wins, ties or loses.
+// Every opponent holds a range and is dealt one of its combos on each runout
+pub fn calc_equity(
+    hand: &Hand,
+    community: &Hand,
+    dead_cards: &[Card],
+    villains: &[Range],
+    iterations: u32,
+    seed: u64,
+) -> Result<Equity, PokerProbError> {
+    let mut known_cards: Vec<Card> = hand.cards().to_vec();
+    known_cards.extend_from_slice(community.cards());
+    known_cards.extend_from_slice(dead_cards);
+
+    // Card removal against our hand, the board and the dead cards
+    let villains: Vec<Range> = villains
+        .iter()
+        .map(|range| range.remove_cards(&known_cards))
+        .collect();
+    if let Some(i) = villains.iter().position(|range| range.is_empty()) {
+        return Err(PokerProbError::InvalidRange(format!(
+            "Villain {} has no combos left once our hand, the board and the dead cards are removed",
+            i + 1
+        )));
+    }
+
+    // Sort the unknown cards so the same seed always gives the same runouts
+    let mut unknown_cards: Vec<Card> =
+        calc_prob::get_unknown_cards(Game::Holdem, hand, community, dead_cards)?
+            .iter()
+            .copied()
+            .collect();
+    unknown_cards.sort();
+
+    let num_cards_to_come = 5 - community.len().min(5);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut wins: u32 = 0;
+    let mut ties: u32 = 0;
+    let mut num_runouts: u32 = 0;
+    let mut villain_cards: Vec<Card> = Vec::new();
+    let mut remaining_cards: Vec<Card>;
+    let mut board: Vec<Card>;
+    let mut all_cards: Vec<Card>;
+
+    for _ in 0..iterations {
+        // Deal a combo to every opponent, skip the runout when the ranges can't all be dealt
+        villain_cards.clear();
+        let mut all_dealt = true;
+        for range in &villains {
+            match deal_combo(range, &villain_cards, &mut rng) {
+                Some(cards) => villain_cards.extend_from_slice(&cards),
+                None => {
+                    all_dealt = false;
+                    break;
+                }
+            }
+        }
+        if !all_dealt {
+            continue;
+        }
+
+        remaining_cards = unknown_cards
+            .iter()
+            .filter(|card| !villain_cards.contains(card))
+            .copied()
+            .collect();
+        let (runout, _) = remaining_cards.partial_shuffle(&mut rng, num_cards_to_come);
+        board = community.cards().to_vec();
+        board.extend_from_slice(runout);
+
+        all_cards = hand.cards().to_vec();
+        all_cards.extend_from_slice(&board);
+        let my_rank = all_cards.rank();
+
+        let mut best_villain_rank = None;
+        for cards in villain_cards.chunks(2) {
+            all_cards = cards.to_vec();
+            all_cards.extend_from_slice(&board);
+            best_villain_rank = best_villain_rank.max(Some(all_cards.rank()));
+        }
+
+        match best_villain_rank {
+            Some(villain_rank) if villain_rank > my_rank => {}
+            Some(villain_rank) if villain_rank == my_rank => ties += 1,
+            _ => wins += 1,
+        }
+        num_runouts += 1;
+    }
+
+    if num_runouts == 0 {
+        return Err(PokerProbError::InvalidRange(
+            "The villain ranges can't be dealt together, every combo shares a card with another villain"
+                .to_string(),
+        ));
+    }
+
+    let win = wins as f64 * 100.0 / num_runouts as f64;
+    let tie = ties as f64 * 100.0 / num_runouts as f64;
+    Ok(Equity {
+        win,
+        tie,
+        lose: 100.0 - win - tie,
+    })
+}
+
+// Pick a combo from the range based on its weight, skipping combos that hold an already dealt card
+fn deal_combo(range: &Range, dealt_cards: &[Card], rng: &mut StdRng) -> Option<[Card; 2]> {
+    let available: Vec<&WeightedCombo> = range
+        .combos
+        .iter()
+        .filter(|combo| !combo.cards.iter().any(|card| dealt_cards.contains(card)))
+        .collect();
+    let total_weight: f64 = available.iter().map(|combo| combo.weight).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+
+    let mut target = rng.gen_range(0.0..total_weight);
+    for combo in &available {
+        if target < combo.weight {
+            return Some(combo.cards);
+        }
+        target -= combo.weight;
+    }
+    available.last().map(|combo| combo.cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Overpair against a lower pair on a dry board should win most of the time
+    #[test]
+    fn test_overpair_equity() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let community = Hand::new_from_str("7c2s9d").unwrap();
+        let villains = vec![Range::parse("KhKd").unwrap()];
+        let equity = calc_equity(&hand, &community, &[], &villains, 2000, 1).unwrap();
+        assert!(equity.win > 85.0);
+        assert!((equity.win + equity.tie + equity.lose - 100.0).abs() < 1e-9);
+    }
+
+    // Same hand on both sides with no flush possible always chops the pot
+    #[test]
+    fn test_equity_chop() {
+        let hand = Hand::new_from_str("AhKd").unwrap();
+        let community = Hand::new_from_str("2c7s9d").unwrap();
+        let villains = vec![Range::parse("AsKc").unwrap()];
+        let equity = calc_equity(&hand, &community, &[], &villains, 500, 7).unwrap();
+        assert!(equity.tie > 90.0);
+    }
+
+    // On the river the result is known, the nuts wins every time
+    #[test]
+    fn test_equity_river() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("QhJhTh2c3d").unwrap();
+        let villains = vec![Range::parse("AsAd").unwrap(), Range::parse("2s2d").unwrap()];
+        let equity = calc_equity(&hand, &community, &[], &villains, 100, 3).unwrap();
+        assert_eq!(equity.win, 100.0);
+    }
+
+    // Aces are way ahead of a range of pocket pairs below them
+    #[test]
+    fn test_range_equity() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let community = Hand::new_from_str("Kc7s2d").unwrap();
+        let villains = vec![Range::parse("22-QQ").unwrap()];
+        let equity = calc_equity(&hand, &community, &[], &villains, 2000, 11).unwrap();
+        assert!(equity.win > 80.0);
+    }
+
+    // Every combo of the range is blocked by our hand and the board
+    #[test]
+    fn test_range_equity_blocked_range() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let community = Hand::new_from_str("AsKc2d").unwrap();
+        let villains = vec![Range::parse("AA").unwrap()];
+        assert!(matches!(
+            calc_equity(&hand, &community, &[], &villains, 100, 1),
+            Err(PokerProbError::InvalidRange(_))
+        ));
+    }
+
+    // Two opponents holding the same only combo can't be dealt together
+    #[test]
+    fn test_range_equity_overlapping_ranges() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let community = Hand::new_from_str("Kc7s2d").unwrap();
+        let villains = vec![Range::parse("QsQh").unwrap(), Range::parse("QsQh").unwrap()];
+        assert!(matches!(
+            calc_equity(&hand, &community, &[], &villains, 100, 1),
+            Err(PokerProbError::InvalidRange(_))
+        ));
+    }
+
+    // The same seed should give the same result
+    #[test]
+    fn test_equity_seed_is_reproducible() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let villains = vec![Range::parse("AsAd").unwrap()];
+        assert_eq!(
+            calc_equity(&hand, &community, &[], &villains, 1000, 42).unwrap(),
+            calc_equity(&hand, &community, &[], &villains, 1000, 42).unwrap()
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use crate::game::Game;
+use rs_poker::core::Card;
+use std::error::Error;
+use std::fmt;
+
+/// Everything that can be wrong with the cards or ranges we are given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PokerProbError {
+    /// Text that is not a value followed by a suit, e.g. `1h` or `Ax`.
+    InvalidCard(String),
+    /// A card used more than once in our hand and the community cards.
+    DuplicateCard(Card),
+    /// Card the game does not deal, e.g. a 2 in short deck.
+    CardNotInDeck(Game, Card),
+    /// Number of community cards that is not a street.
+    InvalidBoardSize(usize),
+    /// Number of hole cards that does not fit the game.
+    InvalidHoleCards(Game, usize),
+    /// Community cards given in a game without a board.
+    NoCommunityCards(Game, usize),
+    /// Range of an opponent that can't be parsed, with the reason.
+    InvalidRange(String),
+    /// File that can't be read or written, with the reason.
+    Io(String),
+    /// Number out of its range, e.g. no iterations or a bet of 0, with the reason.
+    InvalidOption(String),
+}
+
+impl fmt::Display for PokerProbError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PokerProbError::InvalidCard(card) => write!(
+                f,
+                "'{}' is not a card, use a value (2-9, T, J, Q, K, A) followed by a suit (c, d, h, s), e.g. Ah",
+                card
+            ),
+            PokerProbError::DuplicateCard(card) => {
+                write!(f, "{} is used more than once", card)
+            }
+            PokerProbError::CardNotInDeck(game, card) => {
+                write!(f, "{} is not in the deck of {}", card, game)
+            }
+            PokerProbError::InvalidBoardSize(num_cards) => write!(
+                f,
+                "{} community cards is not a street, expected 0 (preflop), 3 (flop), 4 (turn) or 5 (river)",
+                num_cards
+            ),
+            PokerProbError::InvalidHoleCards(game, num_cards) => {
+                let expected = game.num_hole_cards();
+                if expected.start() == expected.end() {
+                    write!(f, "{} is played with {} hole cards, got {}", game, expected.start(), num_cards)
+                } else {
+                    write!(
+                        f,
+                        "{} is played with {} to {} hole cards, got {}",
+                        game,
+                        expected.start(),
+                        expected.end(),
+                        num_cards
+                    )
+                }
+            }
+            PokerProbError::NoCommunityCards(game, num_cards) => write!(
+                f,
+                "{} has no community cards, got {}, every card we hold goes in the hand",
+                game, num_cards
+            ),
+            PokerProbError::InvalidRange(reason) => write!(f, "{}", reason),
+            PokerProbError::Io(reason) => write!(f, "{}", reason),
+            PokerProbError::InvalidOption(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl Error for PokerProbError {}
+
+impl PokerProbError {
+    // Exit code of the CLI for each kind of error, 1 and 2 are left to panics and clap
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            PokerProbError::InvalidCard(_) => 3,
+            PokerProbError::DuplicateCard(_) => 4,
+            PokerProbError::InvalidBoardSize(_) => 5,
+            PokerProbError::InvalidHoleCards(..) => 6,
+            PokerProbError::InvalidRange(_) => 7,
+            PokerProbError::Io(_) => 8,
+            PokerProbError::CardNotInDeck(..) => 9,
+            PokerProbError::NoCommunityCards(..) => 10,
+            PokerProbError::InvalidOption(_) => 11,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rs_poker::core::{Suit, Value};
+
+    // Every kind of error exits the CLI with its own code
+    #[test]
+    fn test_distinct_exit_codes() {
+        let errors = [
+            PokerProbError::InvalidCard("1h".to_string()),
+            PokerProbError::DuplicateCard(Card::new(Value::Ace, Suit::Heart)),
+            PokerProbError::InvalidBoardSize(2),
+            PokerProbError::InvalidHoleCards(Game::Holdem, 3),
+            PokerProbError::InvalidRange("Invalid hand 'AAs' in range".to_string()),
+            PokerProbError::Io("No such file or directory".to_string()),
+            PokerProbError::CardNotInDeck(Game::ShortDeck, Card::new(Value::Two, Suit::Heart)),
+            PokerProbError::NoCommunityCards(Game::Stud, 3),
+            PokerProbError::InvalidOption("Iterations should be at least 1, got 0".to_string()),
+        ];
+        let mut exit_codes: Vec<i32> = errors.iter().map(|err| err.exit_code()).collect();
+        exit_codes.sort();
+        exit_codes.dedup();
+        assert_eq!(exit_codes.len(), errors.len());
+        assert!(exit_codes.iter().all(|&code| code > 2));
+    }
+
+    #[test]
+    fn test_duplicate_card_message() {
+        let err = PokerProbError::DuplicateCard(Card::new(Value::Ace, Suit::Heart));
+        assert_eq!(err.to_string(), "Ah is used more than once");
+    }
+}
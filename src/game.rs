@@ -0,0 +1,643 @@
+use crate::calc_prob::{self, HandRank, StraightDraw, Street};
+use crate::error::PokerProbError;
+use rs_poker::core::{Card, CardIter, Deck, Hand, Rank, Rankable, Suit, Value};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Poker variant, it decides how many hole cards we hold and how they make a hand with the board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Game {
+    /// Texas hold'em, the best 5 of our 2 hole cards and the board.
+    #[default]
+    Holdem,
+    /// Pot-limit Omaha with 4, 5 or 6 hole cards, exactly 2 of them and 3 board cards make a hand.
+    Omaha,
+    /// Omaha where the pot is split with the best 8 or better low, made with 2 hole cards and 3 board cards too.
+    #[serde(rename = "omahahilo")]
+    OmahaHiLo,
+    /// Hold'em without the 2s to 5s: 36 cards, A-6-7-8-9 is a straight and a flush beats a full house.
+    #[serde(rename = "shortdeck")]
+    ShortDeck,
+    /// Seven card stud, no board: the best 5 of our 3 to 7 down and up cards.
+    Stud,
+    /// Five card draw, no board: any of our 5 cards can be thrown away and replaced once.
+    Draw,
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Game::Holdem => "Hold'em",
+            Game::Omaha => "Omaha",
+            Game::OmahaHiLo => "Omaha Hi/Lo",
+            Game::ShortDeck => "Short deck",
+            Game::Stud => "Seven card stud",
+            Game::Draw => "Five card draw",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Game {
+    pub fn num_hole_cards(self) -> RangeInclusive<usize> {
+        match self {
+            Game::Holdem | Game::ShortDeck => 2..=2,
+            Game::Omaha | Game::OmahaHiLo => 4..=6,
+            Game::Stud => 3..=7,
+            Game::Draw => 5..=5,
+        }
+    }
+
+    // Function to find the street, stud and draw have no community cards and stud goes by the number of our cards
+    pub fn street(self, hand: &Hand, community: &Hand) -> Result<Street, PokerProbError> {
+        if self != Game::Stud && self != Game::Draw {
+            return Street::from_num_community_cards(community.len());
+        }
+        if !community.is_empty() {
+            return Err(PokerProbError::NoCommunityCards(self, community.len()));
+        }
+        if self == Game::Draw {
+            return Ok(Street::Draw);
+        }
+        match hand.len() {
+            3 => Ok(Street::ThirdStreet),
+            4 => Ok(Street::FourthStreet),
+            5 => Ok(Street::FifthStreet),
+            6 => Ok(Street::SixthStreet),
+            7 => Ok(Street::SeventhStreet),
+            num_cards => Err(PokerProbError::InvalidHoleCards(self, num_cards)),
+        }
+    }
+
+    // Function to count the cards dealt on the next street, or until the end of the hand when all in.
+    // The cards of the draw depend on the discards, they are counted with each discard option.
+    pub fn num_cards_to_come(self, all_in: bool, hand: &Hand, community: &Hand) -> usize {
+        if self == Game::Draw {
+            return 0;
+        }
+        if self == Game::Stud {
+            let num_cards_left = 7 - hand.len().min(7);
+            return if all_in {
+                num_cards_left
+            } else {
+                num_cards_left.min(1)
+            };
+        }
+
+        // When all in, every remaining community card is dealt, otherwise only the next street
+        if community.len() >= 5 {
+            0
+        } else if all_in {
+            5 - community.len()
+        } else if community.is_empty() {
+            3
+        } else {
+            1
+        }
+    }
+
+    // Function to get a brand new deck of the game
+    pub fn deck(self) -> Deck {
+        let mut deck = Deck::default();
+        if self == Game::ShortDeck {
+            for value in [Value::Two, Value::Three, Value::Four, Value::Five] {
+                for suit in Suit::suits() {
+                    deck.remove(&Card::new(value, suit));
+                }
+            }
+        }
+        deck
+    }
+
+    /// Every hand rank from the weakest to the strongest, a flush beats a full house in short deck.
+    pub fn hand_ranks(self) -> [HandRank; 10] {
+        let mut hand_ranks = HandRank::all();
+        if self == Game::ShortDeck {
+            hand_ranks.swap(5, 6);
+        }
+        hand_ranks
+    }
+
+    // Function to compare hand ranks in the order of the game, a higher strength is a better hand
+    pub fn strength(self, hand_rank: HandRank) -> usize {
+        self.hand_ranks()
+            .iter()
+            .position(|&other| other == hand_rank)
+            .expect("Every hand rank should be in the game.")
+    }
+
+    // Function to check that we hold as many hole cards as the game deals
+    pub fn check_hole_cards(self, hand: &Hand) -> Result<(), PokerProbError> {
+        if !self.num_hole_cards().contains(&hand.len()) {
+            return Err(PokerProbError::InvalidHoleCards(self, hand.len()));
+        }
+        Ok(())
+    }
+
+    // Function to rank the best hand made with our hole cards and the board.
+    // The rank of rs_poker follows the hold'em order, use strength to compare hand ranks of short deck.
+    pub fn rank(self, hole_cards: &[Card], board: &[Card]) -> Rank {
+        match self {
+            Game::Holdem | Game::Stud | Game::Draw => {
+                let mut all_cards: Vec<Card> = hole_cards.to_vec();
+                all_cards.extend_from_slice(board);
+                all_cards.rank()
+            }
+            Game::ShortDeck => {
+                let mut all_cards: Vec<Card> = hole_cards.to_vec();
+                all_cards.extend_from_slice(board);
+                let rank = all_cards.rank();
+
+                // rs_poker doesn't know the A-6-7-8-9 straight, it is the lowest straight of short deck
+                let has_wheel = |cards: &mut dyn Iterator<Item = &Card>| {
+                    let values: Vec<Value> = cards.map(|card| card.value).collect();
+                    SHORT_DECK_WHEEL.iter().all(|value| values.contains(value))
+                };
+                if Suit::suits()
+                    .iter()
+                    .any(|&suit| has_wheel(&mut all_cards.iter().filter(|card| card.suit == suit)))
+                {
+                    rank.max(Rank::StraightFlush(0))
+                } else if has_wheel(&mut all_cards.iter()) {
+                    rank.max(Rank::Straight(0))
+                } else {
+                    rank
+                }
+            }
+            Game::Omaha | Game::OmahaHiLo => {
+                // Before the flop, there are not 3 board cards yet and only the 2 hole cards count
+                let board_combos: Vec<Vec<Card>> = if board.len() < 3 {
+                    vec![board.to_vec()]
+                } else {
+                    CardIter::new(board, 3).collect()
+                };
+                let mut best_rank = None;
+                let mut all_cards: Vec<Card> = Vec::with_capacity(5);
+                for (i, first_hole) in hole_cards.iter().enumerate() {
+                    for second_hole in &hole_cards[i + 1..] {
+                        for board_combo in &board_combos {
+                            all_cards.clear();
+                            all_cards.push(*first_hole);
+                            all_cards.push(*second_hole);
+                            all_cards.extend_from_slice(board_combo);
+                            // Ranking exactly 5 cards is faster than finding the best 5 of them
+                            let rank = if all_cards.len() == 5 {
+                                all_cards.rank_five()
+                            } else {
+                                all_cards.rank()
+                            };
+                            best_rank = best_rank.max(Some(rank));
+                        }
+                    }
+                }
+                best_rank.expect("Omaha hand should have at least 2 hole cards.")
+            }
+        }
+    }
+
+    pub fn hand_rank(self, hole_cards: &[Card], board: &[Card]) -> HandRank {
+        HandRank::from_rank(&self.rank(hole_cards, board))
+    }
+
+    // Function to find the cards of the best hand, 5 of them or all of our cards when we don't hold 5 yet.
+    // Omaha takes exactly 2 hole cards and 3 board cards, the other games any of the cards.
+    pub fn best_five_cards(self, hole_cards: &[Card], board: &[Card]) -> Vec<Card> {
+        let combos: Vec<Vec<Card>> = match self {
+            Game::Omaha | Game::OmahaHiLo => {
+                let board_combos: Vec<Vec<Card>> = if board.len() < 3 {
+                    vec![board.to_vec()]
+                } else {
+                    CardIter::new(board, 3).collect()
+                };
+                let mut combos = Vec::new();
+                for hole_combo in CardIter::new(hole_cards, 2) {
+                    for board_combo in &board_combos {
+                        let mut cards = hole_combo.clone();
+                        cards.extend_from_slice(board_combo);
+                        combos.push(cards);
+                    }
+                }
+                combos
+            }
+            _ => {
+                let mut all_cards: Vec<Card> = hole_cards.to_vec();
+                all_cards.extend_from_slice(board);
+                if all_cards.len() <= 5 {
+                    vec![all_cards]
+                } else {
+                    CardIter::new(&all_cards, 5).collect()
+                }
+            }
+        };
+
+        // Each combo is already a whole hand, Omaha combos are ranked as hold'em hands
+        let five_card_game = match self {
+            Game::Omaha | Game::OmahaHiLo => Game::Holdem,
+            game => game,
+        };
+        combos
+            .into_iter()
+            .max_by_key(|cards| {
+                let rank = five_card_game.rank(cards, &[]);
+                (self.strength(HandRank::from_rank(&rank)), rank)
+            })
+            .unwrap_or_default()
+    }
+
+    // Function to get the cards that finish with exactly a hand rank on the next card,
+    // every unknown card is tried so the outs mean the same in every game.
+    pub fn calc_outs(
+        self,
+        hand_rank: HandRank,
+        deck: &Deck,
+        hand: &Hand,
+        community: &Hand,
+    ) -> Vec<Card> {
+        // No outs are needed for a hand rank we have already made
+        let strength = self.strength(hand_rank);
+        if self.strength(self.hand_rank(hand.cards(), community.cards())) >= strength {
+            return Vec::new();
+        }
+        let mut unknown_cards: Vec<Card> = deck.iter().copied().collect();
+        unknown_cards.sort_by_key(|card| (card.value, card.suit.to_char()));
+        let mut board: Vec<Card> = community.cards().to_vec();
+        let mut outs = Vec::new();
+        for card in unknown_cards {
+            board.push(card);
+            // Only the cards making exactly this hand rank, like the exact probability
+            if self.hand_rank(hand.cards(), &board) == hand_rank {
+                outs.push(card);
+            }
+            board.pop();
+        }
+        outs
+    }
+
+    // Find which kind of straight draw we have and the values of the cards that complete a straight
+    pub fn straight_draw(self, hand: &Hand, community: &Hand) -> (StraightDraw, Vec<Value>) {
+        match self {
+            Game::Holdem | Game::Stud | Game::Draw => {
+                return calc_prob::get_straight_draw(hand, community)
+            }
+            Game::ShortDeck => return short_deck_straight_draw(hand, community),
+            Game::Omaha | Game::OmahaHiLo => {}
+        }
+
+        let hole_values: Vec<Value> = hand.cards().iter().map(|card| card.value).collect();
+        let mut board_values: Vec<Value> =
+            community.cards().iter().map(|card| card.value).collect();
+        if omaha_straight_high_value(&hole_values, &board_values).is_some() {
+            return (StraightDraw::Made, Vec::new());
+        }
+
+        let mut straight_values = Vec::new();
+        let mut straight_high_values = Vec::new();
+        for value in Value::values() {
+            board_values.push(value);
+            if let Some(high) = omaha_straight_high_value(&hole_values, &board_values) {
+                straight_values.push(value);
+                straight_high_values.push(high);
+            }
+            board_values.pop();
+        }
+        (
+            calc_prob::classify_straight_draw(&straight_values, &straight_high_values),
+            straight_values,
+        )
+    }
+}
+
+const SHORT_DECK_WHEEL: [Value; 5] = [
+    Value::Ace,
+    Value::Six,
+    Value::Seven,
+    Value::Eight,
+    Value::Nine,
+];
+
+// Highest straight made with the values in short deck, where the ace can also play below the 6
+fn short_deck_straight_high_value(values: &[Value]) -> Option<Value> {
+    calc_prob::find_straight_high_value(values).or_else(|| {
+        SHORT_DECK_WHEEL
+            .iter()
+            .all(|value| values.contains(value))
+            .then_some(Value::Nine)
+    })
+}
+
+// Same as the hold'em straight draw, with only the values of the short deck and its wheel
+fn short_deck_straight_draw(hand: &Hand, community: &Hand) -> (StraightDraw, Vec<Value>) {
+    let mut values: Vec<Value> = hand
+        .cards()
+        .iter()
+        .chain(community.cards())
+        .map(|card| card.value)
+        .collect();
+    if short_deck_straight_high_value(&values).is_some() {
+        return (StraightDraw::Made, Vec::new());
+    }
+
+    let mut straight_values = Vec::new();
+    let mut straight_high_values = Vec::new();
+    for value in Value::values() {
+        if value < Value::Six || values.contains(&value) {
+            continue;
+        }
+        values.push(value);
+        if let Some(high) = short_deck_straight_high_value(&values) {
+            straight_values.push(value);
+            straight_high_values.push(high);
+        }
+        values.pop();
+    }
+    (
+        calc_prob::classify_straight_draw(&straight_values, &straight_high_values),
+        straight_values,
+    )
+}
+
+// Highest straight made with exactly 2 of the hole values and 3 of the board values, if any
+fn omaha_straight_high_value(hole_values: &[Value], board_values: &[Value]) -> Option<Value> {
+    let mut best_high = None;
+    for (i, first_hole) in hole_values.iter().enumerate() {
+        for second_hole in &hole_values[i + 1..] {
+            for (j, first_board) in board_values.iter().enumerate() {
+                for (k, second_board) in board_values.iter().enumerate().skip(j + 1) {
+                    for third_board in &board_values[k + 1..] {
+                        let values = [
+                            *first_hole,
+                            *second_hole,
+                            *first_board,
+                            *second_board,
+                            *third_board,
+                        ];
+                        best_high = best_high.max(calc_prob::find_straight_high_value(&values));
+                    }
+                }
+            }
+        }
+    }
+    best_high
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_prob::parse_cards;
+
+    #[test]
+    fn test_check_hole_cards() {
+        assert!(Game::Holdem
+            .check_hole_cards(&parse_cards("AhKd").unwrap())
+            .is_ok());
+        assert_eq!(
+            Game::Holdem.check_hole_cards(&parse_cards("AhKdQc").unwrap()),
+            Err(PokerProbError::InvalidHoleCards(Game::Holdem, 3))
+        );
+        assert!(Game::Omaha
+            .check_hole_cards(&parse_cards("AhKdQcJc").unwrap())
+            .is_ok());
+        assert!(Game::Omaha
+            .check_hole_cards(&parse_cards("AhKdQcJcTs9s").unwrap())
+            .is_ok());
+        assert_eq!(
+            Game::Omaha.check_hole_cards(&parse_cards("AhKd").unwrap()),
+            Err(PokerProbError::InvalidHoleCards(Game::Omaha, 2))
+        );
+    }
+
+    // Four hearts in hand and two on the board would be a flush with every card, not with 3 board cards
+    #[test]
+    fn test_omaha_needs_three_board_cards() {
+        let hand = parse_cards("Ah4h3h2h").unwrap();
+        let board = parse_cards("Kh6h4c").unwrap();
+        assert_eq!(
+            Game::Holdem.hand_rank(hand.cards(), board.cards()),
+            HandRank::Flush
+        );
+        assert_eq!(
+            Game::Omaha.hand_rank(hand.cards(), board.cards()),
+            HandRank::OnePair
+        );
+    }
+
+    // With trips on the board, one king in hand can't make a full house with the king on the board
+    #[test]
+    fn test_omaha_needs_two_hole_cards() {
+        let hand = parse_cards("Kc8d5s2h").unwrap();
+        let board = parse_cards("AhAcAd7sKd").unwrap();
+        assert_eq!(
+            Game::Holdem.hand_rank(hand.cards(), board.cards()),
+            HandRank::FullHouse
+        );
+        assert_eq!(
+            Game::Omaha.hand_rank(hand.cards(), board.cards()),
+            HandRank::ThreeOfAKind
+        );
+    }
+
+    // A single heart in hand can't make a flush with four hearts on the board
+    #[test]
+    fn test_omaha_flush_outs() {
+        let hand = parse_cards("AhKs7c2d").unwrap();
+        let board = parse_cards("QhJh3h").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Omaha, &hand, &board, &[]).unwrap();
+        assert!(Game::Omaha
+            .calc_outs(HandRank::Flush, &deck, &hand, &board)
+            .is_empty());
+
+        let hand = parse_cards("AhTh7c2d").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Omaha, &hand, &board, &[]).unwrap();
+        let outs = Game::Omaha.calc_outs(HandRank::Flush, &deck, &hand, &board);
+        // Already a flush, no outs needed
+        assert!(outs.is_empty());
+    }
+
+    // A made flush needs no outs for the flush or any hand rank below it, the 5h and the Th still make a straight flush
+    #[test]
+    fn test_no_outs_for_made_hand_ranks() {
+        let hand = parse_cards("9h8h").unwrap();
+        let board = parse_cards("7h6h2h").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &board, &[]).unwrap();
+        for hand_rank in [HandRank::OnePair, HandRank::ThreeOfAKind, HandRank::Flush] {
+            assert!(Game::Holdem
+                .calc_outs(hand_rank, &deck, &hand, &board)
+                .is_empty());
+        }
+        assert!(!Game::Holdem
+            .calc_outs(HandRank::StraightFlush, &deck, &hand, &board)
+            .is_empty());
+    }
+
+    // Hold'em outs follow the same rule as the other games, the straight flush cards are not flush outs
+    #[test]
+    fn test_holdem_outs_exact_hand_rank() {
+        let hand = parse_cards("9h8h").unwrap();
+        let board = parse_cards("7h6h2c").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &board, &[]).unwrap();
+        let flush_outs = Game::Holdem.calc_outs(HandRank::Flush, &deck, &hand, &board);
+        assert_eq!(flush_outs, parse_cards("2h3h4hJhQhKhAh").unwrap().cards());
+        assert_eq!(
+            Game::Holdem.calc_outs(HandRank::StraightFlush, &deck, &hand, &board),
+            parse_cards("5hTh").unwrap().cards()
+        );
+        let straight_outs = Game::Holdem.calc_outs(HandRank::Straight, &deck, &hand, &board);
+        assert_eq!(straight_outs.len(), 6);
+    }
+
+    // A wrap is completed by more values than an open-ended draw
+    #[test]
+    fn test_omaha_wrap() {
+        let hand = parse_cards("9h8d6c2s").unwrap();
+        let board = parse_cards("7s5hKd").unwrap();
+        let (draw, values) = Game::Omaha.straight_draw(&hand, &board);
+        assert_eq!(draw, StraightDraw::Wrap);
+        assert_eq!(
+            values,
+            vec![Value::Four, Value::Six, Value::Eight, Value::Nine]
+        );
+
+        // Only the 2 best hole cards count, 9-8 with 7-6 on the board is open-ended
+        let hand = parse_cards("9h8dAc2s").unwrap();
+        let board = parse_cards("7s6hKd").unwrap();
+        assert_eq!(
+            Game::Omaha.straight_draw(&hand, &board).0,
+            StraightDraw::OpenEnded
+        );
+    }
+
+    #[test]
+    fn test_short_deck_deck() {
+        let deck = Game::ShortDeck.deck();
+        assert_eq!(deck.len(), 36);
+        assert!(!deck.contains(&Card::new(Value::Five, Suit::Heart)));
+
+        let hand = parse_cards("AhKh").unwrap();
+        let board = parse_cards("2c7d8s").unwrap();
+        assert_eq!(
+            calc_prob::get_unknown_cards(Game::ShortDeck, &hand, &board, &[]).err(),
+            Some(PokerProbError::CardNotInDeck(
+                Game::ShortDeck,
+                Card::new(Value::Two, Suit::Club)
+            ))
+        );
+    }
+
+    // The ace plays below the 6, and a flush beats a full house
+    #[test]
+    fn test_short_deck_hand_ranks() {
+        let hand = parse_cards("Ah6c").unwrap();
+        let board = parse_cards("7d8s9hKc").unwrap();
+        assert_eq!(
+            Game::ShortDeck.hand_rank(hand.cards(), board.cards()),
+            HandRank::Straight
+        );
+        assert_eq!(
+            Game::Holdem.hand_rank(hand.cards(), board.cards()),
+            HandRank::HighCard
+        );
+
+        let hand = parse_cards("Ah6h").unwrap();
+        let board = parse_cards("7h8h9hKc").unwrap();
+        assert_eq!(
+            Game::ShortDeck.hand_rank(hand.cards(), board.cards()),
+            HandRank::StraightFlush
+        );
+
+        assert!(
+            Game::ShortDeck.strength(HandRank::Flush)
+                > Game::ShortDeck.strength(HandRank::FullHouse)
+        );
+        assert!(
+            Game::Holdem.strength(HandRank::Flush) < Game::Holdem.strength(HandRank::FullHouse)
+        );
+    }
+
+    #[test]
+    fn test_short_deck_straight_draw() {
+        // 6-7-8-9 is completed by the ace below and the ten above
+        let hand = parse_cards("6h7c").unwrap();
+        let board = parse_cards("8d9sKc").unwrap();
+        assert_eq!(
+            Game::ShortDeck.straight_draw(&hand, &board),
+            (StraightDraw::OpenEnded, vec![Value::Ten, Value::Ace])
+        );
+        assert_eq!(
+            Game::Holdem.straight_draw(&hand, &board),
+            (StraightDraw::OpenEnded, vec![Value::Five, Value::Ten])
+        );
+    }
+
+    // Only the cards of the short deck are outs, each of them under the one hand rank it makes:
+    // the Th makes a royal flush and not a flush, and no card makes a full house
+    #[test]
+    fn test_short_deck_outs() {
+        let hand = parse_cards("AhKh").unwrap();
+        let board = parse_cards("QhJh6c").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::ShortDeck, &hand, &board, &[]).unwrap();
+        assert_eq!(deck.len(), 31);
+        let flush_outs = Game::ShortDeck.calc_outs(HandRank::Flush, &deck, &hand, &board);
+        assert_eq!(flush_outs, parse_cards("6h7h8h9h").unwrap().cards());
+        let royal_flush_outs =
+            Game::ShortDeck.calc_outs(HandRank::RoyalFlush, &deck, &hand, &board);
+        assert_eq!(royal_flush_outs, vec![Card::new(Value::Ten, Suit::Heart)]);
+        assert!(Game::ShortDeck
+            .calc_outs(HandRank::FullHouse, &deck, &hand, &board)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_stud_street() {
+        let hand = parse_cards("AhKh7c").unwrap();
+        assert_eq!(
+            Game::Stud.street(&hand, &Hand::default()),
+            Ok(Street::ThirdStreet)
+        );
+        let hand = parse_cards("AhKh7c2d3d4d5s").unwrap();
+        assert_eq!(
+            Game::Stud.street(&hand, &Hand::default()),
+            Ok(Street::SeventhStreet)
+        );
+        let board = parse_cards("2c3c4c").unwrap();
+        assert_eq!(
+            Game::Stud.street(&hand, &board),
+            Err(PokerProbError::NoCommunityCards(Game::Stud, 3))
+        );
+        assert_eq!(
+            Game::Holdem.street(&parse_cards("AhKh").unwrap(), &board),
+            Ok(Street::Flop)
+        );
+    }
+
+    // Stud deals one card per street up to 7th street
+    #[test]
+    fn test_stud_cards_to_come() {
+        let hand = parse_cards("AhKh7c2d").unwrap();
+        let board = Hand::default();
+        assert_eq!(Game::Stud.num_cards_to_come(false, &hand, &board), 1);
+        assert_eq!(Game::Stud.num_cards_to_come(true, &hand, &board), 3);
+        let hand = parse_cards("AhKh7c2d3d4d5s").unwrap();
+        assert_eq!(Game::Stud.num_cards_to_come(true, &hand, &board), 0);
+        assert_eq!(Game::Holdem.num_cards_to_come(true, &hand, &board), 5);
+    }
+
+    // A 5 or a T makes the straight, the upcards of the other players are not outs
+    #[test]
+    fn test_stud_outs() {
+        let hand = parse_cards("9h8h7h6c").unwrap();
+        let dead_cards = parse_cards("Th5h2h6s").unwrap();
+        let deck =
+            calc_prob::get_unknown_cards(Game::Stud, &hand, &Hand::default(), dead_cards.cards())
+                .unwrap();
+        let straight_outs =
+            Game::Stud.calc_outs(HandRank::Straight, &deck, &hand, &Hand::default());
+        assert_eq!(straight_outs.len(), 6);
+        assert!(!straight_outs.contains(&Card::new(Value::Ten, Suit::Heart)));
+        assert_eq!(
+            Game::Stud.straight_draw(&hand, &Hand::default()),
+            (StraightDraw::OpenEnded, vec![Value::Five, Value::Ten])
+        );
+    }
+}
@@ -0,0 +1,431 @@
+use crate::batch::{self, BatchFormat};
+use crate::calc_prob::{self, HandRank, StraightDraw, Street};
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::report::{analyze, AnalyzeOptions, Report};
+use rs_poker::core::{Card, Hand, Rankable};
+use serde::Serialize;
+use std::io::Write;
+
+/// One hand played by hero, read from a PokerStars hand history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandHistory {
+    /// Number of the hand, e.g. `208563466291`.
+    pub id: String,
+    /// Game read from the first line of the hand, e.g. `Pot Limit Omaha`.
+    pub game: Game,
+    /// Name of the player the hole cards were dealt to.
+    pub hero: String,
+    pub hole_cards: Vec<Card>,
+    /// Every street that was dealt, starting with preflop.
+    pub streets: Vec<StreetHistory>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreetHistory {
+    pub street: Street,
+    /// Every community card dealt up to this street.
+    pub board: Vec<Card>,
+    /// What hero did on this street, e.g. `calls $0.50`.
+    pub hero_actions: Vec<String>,
+}
+
+// A hand starts with e.g. `PokerStars Hand #208563466291:` or `PokerStars Zoom Hand #208563466291:`
+fn is_hand_start(line: &str) -> bool {
+    line.starts_with("PokerStars") && line.contains('#')
+}
+
+// Function to read every hand of a PokerStars hand history file, a hand that can't be read keeps its place with the reason
+pub fn parse_hand_histories(text: &str) -> Vec<Result<HandHistory, String>> {
+    let mut hands = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+
+    // The files are saved with a byte order mark
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if is_hand_start(line) && !lines.is_empty() {
+            hands.push(parse_hand_history(&lines));
+            lines.clear();
+        }
+        if is_hand_start(line) || !lines.is_empty() {
+            lines.push(line);
+        }
+    }
+    if !lines.is_empty() {
+        hands.push(parse_hand_history(&lines));
+    }
+    hands
+}
+
+// Function to read the cards between every pair of brackets, e.g. `*** TURN *** [7h 6h 2c] [Kd]`
+fn bracket_cards(line: &str) -> Result<Vec<Card>, PokerProbError> {
+    let cards_str: String = line
+        .split('[')
+        .skip(1)
+        .filter_map(|part| part.split_once(']'))
+        .map(|(cards_str, _)| cards_str)
+        .collect();
+    Ok(calc_prob::parse_cards(&cards_str)?.cards().to_vec())
+}
+
+// Function to read the game from the first line of a hand, only the flop games we rank like PokerStars are replayed.
+// 6+ Hold'em ranks three of a kind above a straight, stud and draw have no flop.
+fn parse_game(first_line: &str) -> Result<Game, String> {
+    let game = if first_line.contains("6+") {
+        None
+    } else if first_line.contains("Omaha Hi/Lo") {
+        Some(Game::OmahaHiLo)
+    } else if first_line.contains("Omaha") {
+        Some(Game::Omaha)
+    } else if first_line.contains("Hold'em") {
+        Some(Game::Holdem)
+    } else {
+        None
+    };
+    game.ok_or_else(|| {
+        // e.g. `7 Card Stud Limit` out of `PokerStars Hand #1:  7 Card Stud Limit ($0.04/$0.08 USD) - 2020/01/05`
+        let name = first_line
+            .split_once(':')
+            .map(|(_, rest)| rest)
+            .unwrap_or(first_line)
+            .split(" (")
+            .next()
+            .unwrap_or_default()
+            .split(" - ")
+            .next()
+            .unwrap_or_default()
+            .trim();
+        format!(
+            "{} can't be replayed, only Hold'em and Omaha hands are supported",
+            name
+        )
+    })
+}
+
+fn parse_hand_history(lines: &[&str]) -> Result<HandHistory, String> {
+    let id = lines[0]
+        .split_once('#')
+        .and_then(|(_, rest)| rest.split(':').next())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let hand_error = |reason: String| format!("Hand #{}: {}", id, reason);
+    let game = parse_game(lines[0]).map_err(hand_error)?;
+
+    let mut hero = String::new();
+    let mut hole_cards = Vec::new();
+    let mut streets: Vec<StreetHistory> = Vec::new();
+    // Actions after the showdown or in the summary don't belong to a street
+    let mut in_street = false;
+
+    for line in lines {
+        let street = if line.starts_with("*** HOLE CARDS ***") {
+            Some(Street::Preflop)
+        } else if line.starts_with("*** FLOP ***") {
+            Some(Street::Flop)
+        } else if line.starts_with("*** TURN ***") {
+            Some(Street::Turn)
+        } else if line.starts_with("*** RIVER ***") {
+            Some(Street::River)
+        } else {
+            None
+        };
+
+        if let Some(street) = street {
+            streets.push(StreetHistory {
+                street,
+                board: bracket_cards(line).map_err(|err| hand_error(err.to_string()))?,
+                hero_actions: Vec::new(),
+            });
+            in_street = true;
+        } else if line.starts_with("***") {
+            in_street = false;
+        } else if let Some(dealt) = line.strip_prefix("Dealt to ") {
+            // Only hero's cards are shown, the other players are listed without brackets
+            if let Some((name, _)) = dealt.split_once(" [") {
+                if hero.is_empty() {
+                    hero = name.to_string();
+                    hole_cards = bracket_cards(line).map_err(|err| hand_error(err.to_string()))?;
+                }
+            }
+        } else if in_street && !hero.is_empty() {
+            if let Some(action) = line.strip_prefix(&format!("{}: ", hero)) {
+                if let Some(street_history) = streets.last_mut() {
+                    street_history.hero_actions.push(action.to_string());
+                }
+            }
+        }
+    }
+
+    if hero.is_empty() {
+        return Err(hand_error("No hole cards dealt to hero".to_string()));
+    }
+    Ok(HandHistory {
+        id,
+        game,
+        hero,
+        hole_cards,
+        streets,
+    })
+}
+
+// Function to analyze the outs of hero on the flop and the turn of a hand, the streets that were not dealt are left out
+pub fn replay(history: &HandHistory) -> Vec<(Street, Result<Report, PokerProbError>)> {
+    let hand = Hand::new_with_cards(history.hole_cards.clone());
+    history
+        .streets
+        .iter()
+        .filter(|street_history| {
+            street_history.street == Street::Flop || street_history.street == Street::Turn
+        })
+        .map(|street_history| {
+            let board = Hand::new_with_cards(street_history.board.clone());
+            (
+                street_history.street,
+                analyze(
+                    &hand,
+                    &board,
+                    &AnalyzeOptions {
+                        game: history.game,
+                        ..AnalyzeOptions::default()
+                    },
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Outs of hero on one street of a hand, the fields that can't be calculated are left empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ReplayRow {
+    pub hand_id: String,
+    pub hero: String,
+    pub street: Option<Street>,
+    pub hand: String,
+    pub board: String,
+    pub current_hand_rank: Option<HandRank>,
+    pub unique_outs: Option<usize>,
+    pub improve_four_and_two_percent: Option<i8>,
+    pub improve_exact_percent: Option<f64>,
+    pub straight_draw: Option<StraightDraw>,
+    /// What hero did on the street, separated by `; `.
+    pub hero_actions: String,
+    /// Why the hand or the street could not be analyzed.
+    pub error: Option<String>,
+}
+
+// Function to replay every hand and write one row per flop and turn, returns the number of rows
+pub fn run_replay<W: Write>(
+    histories: &[Result<HandHistory, String>],
+    writer: W,
+    format: BatchFormat,
+) -> Result<usize, PokerProbError> {
+    let mut rows = Vec::new();
+    for history in histories {
+        let history = match history {
+            Ok(history) => history,
+            Err(err) => {
+                rows.push(ReplayRow {
+                    error: Some(err.clone()),
+                    ..ReplayRow::default()
+                });
+                continue;
+            }
+        };
+
+        for (street, report) in replay(history) {
+            let street_history = history
+                .streets
+                .iter()
+                .find(|street_history| street_history.street == street)
+                .expect("Replayed street should be in the hand.");
+            let mut row = ReplayRow {
+                hand_id: history.id.clone(),
+                hero: history.hero.clone(),
+                street: Some(street),
+                hand: batch::cards_to_string(&history.hole_cards),
+                board: batch::cards_to_string(&street_history.board),
+                hero_actions: street_history.hero_actions.join("; "),
+                ..ReplayRow::default()
+            };
+            match report {
+                Ok(report) => {
+                    row.current_hand_rank = Some(report.current_hand_rank);
+                    row.unique_outs =
+                        Some(report.combined_outs.values().map(|cards| cards.len()).sum());
+                    row.improve_four_and_two_percent = Some(report.improve_prob);
+                    row.improve_exact_percent = Some(report.exact_improve_prob);
+                    row.straight_draw = Some(report.straight_draw);
+                }
+                Err(err) => row.error = Some(err.to_string()),
+            }
+            rows.push(row);
+        }
+    }
+
+    batch::write_rows(writer, &rows, format)?;
+    Ok(rows.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &str = "\u{feff}PokerStars Hand #208563466291:  Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/05 21:31:04 ET
+Table 'Aase II' 6-max Seat #3 is the button
+Seat 1: Villain ($10 in chips)
+Seat 3: Hero ($10.25 in chips)
+Villain: posts small blind $0.05
+*** HOLE CARDS ***
+Dealt to Hero [9h 8h]
+Hero: raises $0.20 to $0.30
+Villain: calls $0.25
+*** FLOP *** [7h 6h 2c]
+Villain: bets $0.40
+Hero: calls $0.40
+*** TURN *** [7h 6h 2c] [Kd]
+Villain: bets $1
+Hero: folds
+Uncalled bet ($1) returned to Villain
+Villain collected $1.46 from pot
+*** SUMMARY ***
+Total pot $1.40 | Rake $0.06
+Board [7h 6h 2c Kd]
+Seat 3: Hero (button) folded on the Turn
+
+
+
+PokerStars Hand #208563466292:  Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/05 21:32:00 ET
+Table 'Aase II' 6-max Seat #1 is the button
+*** HOLE CARDS ***
+Dealt to Hero [7s 2d]
+Hero: folds
+*** SUMMARY ***
+
+PokerStars Hand #208563466293:  Hold'em No Limit ($0.05/$0.10 USD) - 2020/01/05 21:33:00 ET
+*** HOLE CARDS ***
+Dealt to Hero [7s 1d]
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_parse_hand_histories() {
+        let histories = parse_hand_histories(HISTORY);
+        assert_eq!(histories.len(), 3);
+
+        let history = histories[0].as_ref().unwrap();
+        assert_eq!(history.id, "208563466291");
+        assert_eq!(history.game, Game::Holdem);
+        assert_eq!(history.hero, "Hero");
+        assert_eq!(history.hole_cards, bracket_cards("[9h 8h]").unwrap());
+        assert_eq!(history.streets.len(), 3);
+        assert_eq!(
+            history.streets[0].hero_actions,
+            vec!["raises $0.20 to $0.30"]
+        );
+        assert_eq!(history.streets[2].street, Street::Turn);
+        assert_eq!(
+            history.streets[2].board,
+            bracket_cards("[7h 6h 2c Kd]").unwrap()
+        );
+        assert_eq!(history.streets[2].hero_actions, vec!["folds"]);
+
+        // Folded preflop, only the preflop is dealt
+        assert_eq!(histories[1].as_ref().unwrap().streets.len(), 1);
+        assert!(histories[2]
+            .as_ref()
+            .unwrap_err()
+            .starts_with("Hand #208563466293"));
+    }
+
+    // The cards of the other players are not shown
+    #[test]
+    fn test_no_hero() {
+        let history =
+            "PokerStars Hand #1: Hold'em No Limit\n*** HOLE CARDS ***\nDealt to Villain\n";
+        assert_eq!(
+            parse_hand_histories(history),
+            vec![Err("Hand #1: No hole cards dealt to hero".to_string())]
+        );
+    }
+
+    // Stud, draw and 6+ Hold'em keep their place with an error instead of being read as Hold'em
+    #[test]
+    fn test_unsupported_games() {
+        let history =
+            "PokerStars Hand #3:  7 Card Stud Limit ($0.04/$0.08 USD) - 2020/01/05 21:34:00 ET
+*** 3rd STREET ***
+Dealt to Hero [9h 8h 7h]
+PokerStars Hand #4:  6+ Hold'em Button Blind ($0.05/$0.10 USD) - 2020/01/05 21:35:00 ET
+*** HOLE CARDS ***
+Dealt to Hero [9h 8h]
+PokerStars Hand #5:  Triple Draw 2-7 Lowball Limit ($0.10/$0.20 USD) - 2020/01/05 21:36:00 ET
+*** DEALING HANDS ***
+Dealt to Hero [9h 8h 7h 6c 2d]
+";
+        assert_eq!(
+            parse_hand_histories(history),
+            vec![
+                Err("Hand #3: 7 Card Stud Limit can't be replayed, only Hold'em and Omaha hands are supported".to_string()),
+                Err("Hand #4: 6+ Hold'em Button Blind can't be replayed, only Hold'em and Omaha hands are supported".to_string()),
+                Err("Hand #5: Triple Draw 2-7 Lowball Limit can't be replayed, only Hold'em and Omaha hands are supported".to_string()),
+            ]
+        );
+
+        let mut output: Vec<u8> = Vec::new();
+        let num_rows = run_replay(
+            &parse_hand_histories(history),
+            &mut output,
+            BatchFormat::Csv,
+        )
+        .unwrap();
+        assert_eq!(num_rows, 3);
+    }
+
+    #[test]
+    fn test_replay_omaha() {
+        let history = "PokerStars Hand #2: Omaha Pot Limit ($0.05/$0.10 USD)
+*** HOLE CARDS ***
+Dealt to Hero [Ah Kh Qh 2h]
+*** FLOP *** [Jh 7c 2c]
+Hero: checks
+";
+        let histories = parse_hand_histories(history);
+        let history = histories[0].as_ref().unwrap();
+        assert_eq!(history.game, Game::Omaha);
+        let replayed = replay(history);
+        assert_eq!(
+            replayed[0].1.as_ref().unwrap().current_hand_rank,
+            HandRank::OnePair
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let histories = parse_hand_histories(HISTORY);
+        let replayed = replay(histories[0].as_ref().unwrap());
+        assert_eq!(replayed.len(), 2);
+        assert_eq!(replayed[0].0, Street::Flop);
+        assert_eq!(
+            replayed[0].1.as_ref().unwrap().straight_draw,
+            StraightDraw::OpenEnded
+        );
+        assert_eq!(replayed[1].0, Street::Turn);
+        assert!(replay(histories[1].as_ref().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn test_run_replay() {
+        let histories = parse_hand_histories(HISTORY);
+        let mut output: Vec<u8> = Vec::new();
+        let num_rows = run_replay(&histories, &mut output, BatchFormat::Csv).unwrap();
+        assert_eq!(num_rows, 3);
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines[0].starts_with("hand_id,hero,street,hand,board"));
+        assert!(lines[1].starts_with("208563466291,Hero,flop,9h8h,7h6h2c,high_card,29,"));
+        assert!(lines[1].ends_with(",calls $0.40,"));
+        assert!(lines[3].contains("is not a card"));
+    }
+}
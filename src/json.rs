@@ -0,0 +1,497 @@
+use crate::calc_prob::{self, HandRank, StraightDraw, Street};
+use crate::equity::Equity;
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::low::LowHand;
+use crate::pot_odds::PotOdds;
+use crate::range::Range;
+use crate::report::{analyze, AnalyzeOptions, Report};
+use rs_poker::core::{Card, Rankable};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version of the JSON report. It goes up whenever a field is renamed, removed or changes meaning,
+/// adding a field keeps the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Report in the shape printed by `--format json`, cards are written as text such as `Ah`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonReport {
+    pub schema_version: u32,
+    pub game: Game,
+    pub hand: Vec<String>,
+    pub board: Vec<String>,
+    pub dead_cards: Vec<String>,
+    pub street: Street,
+    pub all_in: bool,
+    pub current_hand_rank: HandRank,
+    pub made_hand: JsonMadeHand,
+    /// One entry per hand rank, from the weakest to the strongest in the order of the game,
+    /// a flush is above a full house in short deck.
+    pub hand_ranks: Vec<JsonHandRank>,
+    pub improve: JsonImprove,
+    pub straight_draw: JsonStraightDraw,
+    /// Only when the pot and the bet are given, on the flop or the turn.
+    pub pot_odds: Option<PotOdds>,
+    /// Only when opponents are given.
+    pub villains: Option<JsonVillains>,
+    /// Only in draw.
+    pub draw: Option<JsonDraw>,
+    /// Only in hi/lo games.
+    pub low: Option<JsonLow>,
+    /// Only preflop in hold'em.
+    pub preflop: Option<JsonPreflop>,
+}
+
+/// Best 5 cards on the table, the cards making the hand rank first.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonMadeHand {
+    pub hand_rank: HandRank,
+    pub cards: Vec<String>,
+    pub kickers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonHandRank {
+    pub hand_rank: HandRank,
+    pub outs: usize,
+    pub out_cards: Vec<String>,
+    /// Probability with the rule of 4 and 2, in percent.
+    pub four_and_two_percent: i8,
+    /// Probability from going through every runout, in percent.
+    pub exact_percent: f64,
+}
+
+/// Unique outs to improve on the current hand rank.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonImprove {
+    pub outs: usize,
+    pub four_and_two_percent: i8,
+    pub exact_percent: f64,
+    /// Out cards under the best hand rank they make, from the best hand rank down.
+    pub by_hand_rank: Vec<JsonOutCards>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonOutCards {
+    pub hand_rank: HandRank,
+    pub out_cards: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonStraightDraw {
+    pub kind: StraightDraw,
+    /// Values that complete the draw, e.g. `5` and `T`.
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonVillains {
+    /// Number of combos of each opponent after card removal.
+    pub combos: Vec<usize>,
+    /// Only on the flop and the turn.
+    pub true_outs: Option<JsonTrueOuts>,
+    pub equity: Option<Equity>,
+    pub iterations: u32,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonTrueOuts {
+    pub clean_outs: Vec<String>,
+    pub dirty_outs: Vec<JsonDirtyOut>,
+    pub discounted_outs: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonDirtyOut {
+    pub card: String,
+    /// Share of the hands of the opponents we are still ahead of, from 0 to 1.
+    pub ahead_share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonDraw {
+    pub target: HandRank,
+    /// Every discard option, from the best chance of making the target down.
+    pub options: Vec<JsonDiscardOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonDiscardOption {
+    pub keep: Vec<String>,
+    pub discard: Vec<String>,
+    /// Probability of finishing with the target or better, in percent.
+    pub target_percent: f64,
+    /// Probability of finishing with each hand rank after the draw, in percent.
+    pub exact_percent: BTreeMap<HandRank, f64>,
+}
+
+/// Low half of the pot, lows are written from the highest card down, e.g. `8-6-4-2-A`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonLow {
+    pub current: Option<LowHand>,
+    pub nut_low: Option<LowHand>,
+    pub is_nut: bool,
+    /// Probability that the board allows a low, in percent.
+    pub possible_percent: f64,
+    /// Probability of finishing with a low, in percent.
+    pub exact_percent: f64,
+    pub outs: usize,
+    pub out_cards: Vec<String>,
+    pub counterfeit_cards: Vec<String>,
+    pub counterfeit_percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonPreflop {
+    /// Starting hand such as `AKs`, `T9o` or `77`.
+    pub starting_hand: String,
+    pub chen_score: i32,
+    /// From 1 to 8, 9 for the hands in none of the groups.
+    pub sklansky_group: u8,
+    /// All-in equity against 1 to 9 random hands, from the tables shipped with the crate.
+    pub equity_vs_random: Vec<JsonRandomEquity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JsonRandomEquity {
+    pub opponents: usize,
+    pub equity: Equity,
+}
+
+/// One spot to analyze, e.g. `{"hand": "AhKh", "board": "7h6h2c", "villains": ["TT+"], "pot": 100, "bet": 50}`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub game: Game,
+    pub hand: String,
+    #[serde(default)]
+    pub board: String,
+    /// Cards seen elsewhere, e.g. the upcards of the other players in stud.
+    #[serde(default)]
+    pub dead_cards: String,
+    #[serde(default)]
+    pub all_in: bool,
+    /// Hands or ranges of the opponents.
+    #[serde(default)]
+    pub villains: Vec<String>,
+    pub pot: Option<f64>,
+    pub bet: Option<f64>,
+    pub effective_stack: Option<f64>,
+    /// Hand rank to aim for with the discards in draw.
+    pub draw_target: Option<HandRank>,
+}
+
+impl Scenario {
+    // Function to parse the ranges of the opponents and the dead cards into the options of the analysis
+    pub fn to_options(&self, iterations: u32, seed: u64) -> Result<AnalyzeOptions, PokerProbError> {
+        let villains = self
+            .villains
+            .iter()
+            .map(|villain| Range::parse(villain).map_err(PokerProbError::InvalidRange))
+            .collect::<Result<_, _>>()?;
+        Ok(AnalyzeOptions {
+            game: self.game,
+            all_in: self.all_in,
+            villains,
+            iterations,
+            seed,
+            pot: self.pot,
+            bet: self.bet,
+            effective_stack: self.effective_stack,
+            dead_cards: calc_prob::parse_cards(&self.dead_cards)?.cards().to_vec(),
+            draw_target: self.draw_target,
+        })
+    }
+}
+
+/// Spot to analyze sent as JSON to the HTTP API or the WebAssembly module,
+/// e.g. `{"hand": "AhKh", "board": "7h6h2c", "villains": ["TT+"], "seed": 1}`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct JsonRequest {
+    #[serde(flatten)]
+    pub scenario: Scenario,
+    /// Number of runouts to simulate for equity, a default of the caller when not given.
+    pub iterations: Option<u32>,
+    /// Seed of the equity simulation, random by default.
+    pub seed: Option<u64>,
+}
+
+/// Most iterations a request can ask for, enough for the equity to be within a fraction of a percent.
+pub const MAX_REQUEST_ITERATIONS: u32 = 1_000_000;
+
+impl JsonRequest {
+    // Function to turn down the requests that would keep the HTTP server or the browser busy for seconds.
+    // The default iterations are set by whoever runs the server or the page, they are not capped.
+    pub fn check_cost(&self) -> Result<(), PokerProbError> {
+        if self.iterations.unwrap_or_default() > MAX_REQUEST_ITERATIONS {
+            return Err(PokerProbError::InvalidOption(format!(
+                "A request runs at most {} iterations, got {}",
+                MAX_REQUEST_ITERATIONS,
+                self.iterations.unwrap_or_default()
+            )));
+        }
+        // Going through every board of Omaha preflop all in takes seconds, it is left to the CLI
+        let scenario = &self.scenario;
+        if matches!(scenario.game, Game::Omaha | Game::OmahaHiLo)
+            && scenario.all_in
+            && scenario.board.trim().is_empty()
+        {
+            return Err(PokerProbError::InvalidOption(format!(
+                "{} preflop all in is too slow for a request, use the CLI",
+                scenario.game
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn analyze(&self, default_iterations: u32) -> Result<JsonReport, PokerProbError> {
+        self.check_cost()?;
+        let options = self.scenario.to_options(
+            self.iterations.unwrap_or(default_iterations),
+            self.seed.unwrap_or_else(rand::random),
+        )?;
+        let hand = calc_prob::parse_cards(&self.scenario.hand)?;
+        let board = calc_prob::parse_cards(&self.scenario.board)?;
+        let report = analyze(&hand, &board, &options)?;
+        Ok(JsonReport::new(&report, &options))
+    }
+}
+
+fn cards_to_strings(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(|card| card.to_string()).collect()
+}
+
+impl JsonReport {
+    pub fn new(report: &Report, options: &AnalyzeOptions) -> JsonReport {
+        let hand_ranks = report
+            .hand_ranks
+            .iter()
+            .map(|hand_rank_report| JsonHandRank {
+                hand_rank: hand_rank_report.hand_rank,
+                outs: hand_rank_report.outs.len(),
+                out_cards: cards_to_strings(&hand_rank_report.outs),
+                four_and_two_percent: hand_rank_report.four_and_two_prob,
+                exact_percent: hand_rank_report.exact_prob,
+            })
+            .collect();
+
+        let improve = JsonImprove {
+            outs: report.combined_outs.values().map(|cards| cards.len()).sum(),
+            four_and_two_percent: report.improve_prob,
+            exact_percent: report.exact_improve_prob,
+            by_hand_rank: report
+                .combined_outs_best_first()
+                .into_iter()
+                .map(|(hand_rank, cards)| JsonOutCards {
+                    hand_rank,
+                    out_cards: cards_to_strings(cards),
+                })
+                .collect(),
+        };
+
+        let villains = if options.villains.is_empty() {
+            None
+        } else {
+            Some(JsonVillains {
+                combos: report.villain_combos.clone(),
+                true_outs: report.true_outs.as_ref().map(|true_outs| JsonTrueOuts {
+                    clean_outs: cards_to_strings(&true_outs.clean_outs),
+                    dirty_outs: true_outs
+                        .dirty_outs
+                        .iter()
+                        .map(|(card, share)| JsonDirtyOut {
+                            card: card.to_string(),
+                            ahead_share: *share,
+                        })
+                        .collect(),
+                    discounted_outs: true_outs.discounted_outs,
+                }),
+                equity: report.equity,
+                iterations: options.iterations,
+                seed: options.seed,
+            })
+        };
+
+        JsonReport {
+            schema_version: SCHEMA_VERSION,
+            game: report.game,
+            hand: cards_to_strings(&report.hand),
+            board: cards_to_strings(&report.board),
+            dead_cards: cards_to_strings(&report.dead_cards),
+            street: report.street,
+            all_in: options.all_in,
+            current_hand_rank: report.current_hand_rank,
+            made_hand: JsonMadeHand {
+                hand_rank: report.made_hand.hand_rank,
+                cards: cards_to_strings(&report.made_hand.cards),
+                kickers: cards_to_strings(&report.made_hand.kickers),
+            },
+            hand_ranks,
+            improve,
+            straight_draw: JsonStraightDraw {
+                kind: report.straight_draw,
+                values: report
+                    .straight_values
+                    .iter()
+                    .map(|value| value.to_char().to_string())
+                    .collect(),
+            },
+            pot_odds: report.pot_odds,
+            villains,
+            draw: report.draw.as_ref().map(|draw| JsonDraw {
+                target: draw.target,
+                options: draw
+                    .options
+                    .iter()
+                    .map(|option| JsonDiscardOption {
+                        keep: cards_to_strings(&option.keep),
+                        discard: cards_to_strings(&option.discard),
+                        target_percent: option.target_prob,
+                        exact_percent: option.exact_probs.clone(),
+                    })
+                    .collect(),
+            }),
+            low: report.low.as_ref().map(|low| JsonLow {
+                current: low.current,
+                nut_low: low.nut_low,
+                is_nut: low.is_nut(),
+                possible_percent: low.possible_prob,
+                exact_percent: low.exact_prob,
+                outs: low.outs.len(),
+                out_cards: cards_to_strings(&low.outs),
+                counterfeit_cards: cards_to_strings(&low.counterfeit_cards),
+                counterfeit_percent: low.counterfeit_prob,
+            }),
+            preflop: report.preflop.as_ref().map(|preflop| JsonPreflop {
+                starting_hand: preflop.starting_hand.clone(),
+                chen_score: preflop.chen_score,
+                sklansky_group: preflop.sklansky_group,
+                equity_vs_random: preflop
+                    .equity_vs_random
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &equity)| JsonRandomEquity {
+                        opponents: i + 1,
+                        equity,
+                    })
+                    .collect(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rs_poker::core::Hand;
+
+    #[test]
+    fn test_json_report() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let options = AnalyzeOptions::default();
+        let report = analyze(&hand, &board, &options).unwrap();
+        let json = serde_json::to_value(JsonReport::new(&report, &options)).unwrap();
+
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["game"], "holdem");
+        assert_eq!(json["street"], "flop");
+        assert_eq!(json["current_hand_rank"], "high_card");
+        assert_eq!(json["made_hand"]["cards"][0], "9h");
+        assert_eq!(json["made_hand"]["kickers"].as_array().unwrap().len(), 4);
+        assert_eq!(json["hand_ranks"].as_array().unwrap().len(), 10);
+        assert_eq!(json["hand_ranks"][8]["hand_rank"], "straight_flush");
+        assert_eq!(json["improve"]["by_hand_rank"][0]["out_cards"][0], "5h");
+        assert_eq!(json["straight_draw"]["kind"], "open_ended");
+        assert!(json["pot_odds"].is_null());
+        assert!(json["villains"].is_null());
+        assert!(json["draw"].is_null());
+        assert!(json["low"].is_null());
+        assert!(json["preflop"].is_null());
+    }
+
+    #[test]
+    fn test_json_report_with_villains() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let options = AnalyzeOptions {
+            villains: vec![crate::range::Range::parse("22,A7s").unwrap()],
+            iterations: 100,
+            seed: 3,
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        let json = serde_json::to_value(JsonReport::new(&report, &options)).unwrap();
+
+        assert_eq!(json["villains"]["combos"][0], 6);
+        assert_eq!(json["villains"]["seed"], 3);
+        // The 2h leaves 2s2d with quads, we are still ahead of the three A7s combos
+        assert_eq!(json["villains"]["true_outs"]["dirty_outs"][0]["card"], "2h");
+        assert_eq!(
+            json["villains"]["true_outs"]["dirty_outs"][0]["ahead_share"],
+            0.75
+        );
+        assert!(json["villains"]["equity"]["win"].is_number());
+    }
+
+    // The same requests are turned down by the HTTP API and the WebAssembly module
+    #[test]
+    fn test_json_request_cost() {
+        let request: JsonRequest =
+            serde_json::from_str("{\"hand\": \"AhKh\", \"iterations\": 1000001}").unwrap();
+        assert!(matches!(
+            request.analyze(100),
+            Err(PokerProbError::InvalidOption(_))
+        ));
+
+        let request: JsonRequest = serde_json::from_str(
+            "{\"game\": \"omahahilo\", \"hand\": \"AhKhQd2c\", \"all_in\": true}",
+        )
+        .unwrap();
+        assert_eq!(
+            request.check_cost(),
+            Err(PokerProbError::InvalidOption(
+                "Omaha Hi/Lo preflop all in is too slow for a request, use the CLI".to_string()
+            ))
+        );
+        // The flop is dealt, only 2 more cards are left to go through
+        let request: JsonRequest = serde_json::from_str(
+            "{\"game\": \"omaha\", \"hand\": \"AhKhQd2c\", \"board\": \"7h6h2c\", \"all_in\": true}",
+        )
+        .unwrap();
+        assert!(request.check_cost().is_ok());
+    }
+
+    #[test]
+    fn test_json_request() {
+        let request: JsonRequest = serde_json::from_str(
+            "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"villains\": [\"2s2d\"], \"seed\": 3}",
+        )
+        .unwrap();
+        assert_eq!(request.scenario.villains, vec!["2s2d"]);
+        assert_eq!(request.iterations, None);
+
+        let json_report = request.analyze(100).unwrap();
+        let villains = json_report.villains.unwrap();
+        assert_eq!(villains.iterations, 100);
+        assert_eq!(villains.seed, 3);
+    }
+
+    // Drawing one card to the flush is the best way to make a flush
+    #[test]
+    fn test_json_draw_request() {
+        let request: JsonRequest = serde_json::from_str(
+            "{\"game\": \"draw\", \"hand\": \"Ah9h7h4hKc\", \"draw_target\": \"flush\"}",
+        )
+        .unwrap();
+        let json = serde_json::to_value(request.analyze(100).unwrap()).unwrap();
+
+        assert_eq!(json["street"], "draw");
+        assert_eq!(json["draw"]["target"], "flush");
+        assert_eq!(json["draw"]["options"].as_array().unwrap().len(), 32);
+        assert_eq!(json["draw"]["options"][0]["discard"][0], "Kc");
+        assert!(json["draw"]["options"][0]["exact_percent"]["flush"].is_number());
+    }
+}
@@ -0,0 +1,50 @@
+//! Outs and probabilities of poker hands, based on the rule of 4 and 2 and on going through every runout.
+//!
+//! `analyze` checks the cards and calculates everything the `poker_prob` CLI prints:
+//!
+//! ```
+//! use poker_prob::{analyze, AnalyzeOptions, HandRank};
+//! use rs_poker::core::Hand;
+//!
+//! let hand = Hand::new_from_str("9h8h").unwrap();
+//! let board = Hand::new_from_str("7h6h2c").unwrap();
+//! let report = analyze(&hand, &board, &AnalyzeOptions::default()).unwrap();
+//! assert_eq!(report.current_hand_rank, HandRank::HighCard);
+//! ```
+
+#[cfg(feature = "cli")]
+pub mod batch;
+pub mod calc_prob;
+pub mod draw;
+pub mod equity;
+pub mod error;
+pub mod game;
+#[cfg(feature = "cli")]
+pub mod hand_history;
+pub mod json;
+pub mod low;
+pub mod pot_odds;
+pub mod preflop;
+pub mod range;
+pub mod repl;
+pub mod report;
+#[cfg(feature = "cli")]
+pub mod server;
+pub mod true_outs;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use calc_prob::{
+    calc_combined_outs, get_flush_outs, get_four_of_a_kind_outs, get_full_house_outs,
+    get_high_card_outs, get_one_pair_outs, get_royal_flush_outs, get_straight_draw,
+    get_straight_flush_outs, get_straight_outs, get_three_of_a_kind_outs, get_two_pairs_outs,
+    get_unknown_cards, parse_cards, HandRank, MadeHand, StraightDraw, Street,
+};
+pub use draw::{DiscardOption, DrawReport};
+pub use error::PokerProbError;
+pub use game::Game;
+pub use json::{JsonReport, JsonRequest, Scenario, SCHEMA_VERSION};
+pub use low::{LowHand, LowReport};
+pub use preflop::PreflopReport;
+pub use range::Range;
+pub use report::{analyze, AnalyzeOptions, HandRankReport, Report};
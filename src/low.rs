@@ -0,0 +1,255 @@
+use crate::game::Game;
+use rs_poker::core::{Card, CardIter, Deck, Hand, Rankable, Value};
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// Values of a qualifying low from the highest down, the ace counts as 1.
+/// A lower low is a better one, so the nut low compares below every other low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LowHand(pub [u8; 5]);
+
+impl fmt::Display for LowHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<String> = self
+            .0
+            .iter()
+            .map(|&value| match value {
+                1 => "A".to_string(),
+                value => value.to_string(),
+            })
+            .collect();
+        write!(f, "{}", values.join("-"))
+    }
+}
+
+// Written as in the text output, e.g. `8-6-4-2-A`
+impl Serialize for LowHand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Low half of the pot in a hi/lo game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowReport {
+    /// Our best qualifying low with the cards on table.
+    pub current: Option<LowHand>,
+    /// Best low any hand can make with the board, none while the board has less than 3 low cards.
+    pub nut_low: Option<LowHand>,
+    /// Exact probability that the board has 3 different low cards after the cards to come, in percent.
+    pub possible_prob: f64,
+    /// Exact probability of finishing with a qualifying low, in percent.
+    pub exact_prob: f64,
+    /// Cards that make or improve our low on the next card, only on the flop and the turn.
+    pub outs: Vec<Card>,
+    /// Cards that take the nut low away from us on the next card, e.g. by pairing one of our low hole cards.
+    pub counterfeit_cards: Vec<Card>,
+    /// Probability that the next card counterfeits our low, in percent.
+    pub counterfeit_prob: f64,
+}
+
+impl LowReport {
+    pub fn is_nut(&self) -> bool {
+        self.current.is_some() && self.current == self.nut_low
+    }
+}
+
+// Value of a card in a low, only the ace to the 8 qualify
+fn low_value(value: Value) -> Option<u8> {
+    match value {
+        Value::Ace => Some(1),
+        value if value <= Value::Eight => Some(value as u8 + 2),
+        _ => None,
+    }
+}
+
+// Different low values of the cards, from the lowest up
+fn low_values(cards: &[Card]) -> Vec<u8> {
+    let mut values: Vec<u8> = cards
+        .iter()
+        .filter_map(|card| low_value(card.value))
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+// Function to find the best low made with exactly 2 different hole values and 3 different board values
+fn best_low_of_values(hole_values: &[u8], board_values: &[u8]) -> Option<LowHand> {
+    let mut best_low: Option<LowHand> = None;
+    for (i, &first_hole) in hole_values.iter().enumerate() {
+        for &second_hole in &hole_values[i + 1..] {
+            let mut free_board_values = board_values
+                .iter()
+                .filter(|&&value| value != first_hole && value != second_hole);
+            // The board values are sorted, the 3 lowest of them make the best low with these hole values
+            let (Some(&first), Some(&second), Some(&third)) = (
+                free_board_values.next(),
+                free_board_values.next(),
+                free_board_values.next(),
+            ) else {
+                continue;
+            };
+            let mut values = [first_hole, second_hole, first, second, third];
+            values.sort_by(|a, b| b.cmp(a));
+            let low = LowHand(values);
+            if best_low.is_none_or(|best| low < best) {
+                best_low = Some(low);
+            }
+        }
+    }
+    best_low
+}
+
+// Function to find our best 8 or better low, made with exactly 2 hole cards and 3 board cards
+pub fn best_low(hole_cards: &[Card], board: &[Card]) -> Option<LowHand> {
+    best_low_of_values(&low_values(hole_cards), &low_values(board))
+}
+
+// Function to find the best low any 2 hole cards can make with the board
+pub fn nut_low(board: &[Card]) -> Option<LowHand> {
+    let all_values: Vec<u8> = (1..=8).collect();
+    best_low_of_values(&all_values, &low_values(board))
+}
+
+// Function to calculate the low half of the pot: what we have, the nut low, the chance of a low
+// over every runout, and the cards that make or counterfeit our low on the next card
+pub fn calc_low(
+    game: Game,
+    all_in: bool,
+    deck: &Deck,
+    hand: &Hand,
+    community: &Hand,
+    has_outs: bool,
+) -> LowReport {
+    // Sort the unknown cards so the enumeration does not depend on the hash order of the deck
+    let mut unknown_cards: Vec<Card> = deck.iter().copied().collect();
+    unknown_cards.sort();
+
+    let current = best_low(hand.cards(), community.cards());
+    let num_cards_to_come = game.num_cards_to_come(all_in, hand, community);
+
+    let mut num_runouts: u32 = 0;
+    let mut num_possible: u32 = 0;
+    let mut num_lows: u32 = 0;
+    let mut count_runout = |board: &[Card]| {
+        num_runouts += 1;
+        if low_values(board).len() >= 3 {
+            num_possible += 1;
+            if best_low(hand.cards(), board).is_some() {
+                num_lows += 1;
+            }
+        }
+    };
+    if num_cards_to_come == 0 {
+        count_runout(community.cards());
+    } else {
+        let mut board: Vec<Card>;
+        for runout in CardIter::new(&unknown_cards, num_cards_to_come) {
+            board = community.cards().to_vec();
+            board.extend(runout);
+            count_runout(&board);
+        }
+    }
+
+    // Our low can only get better with more board cards, but the nut low can get better than ours
+    let has_nut_low = current.is_some() && current == nut_low(community.cards());
+    let mut outs = Vec::new();
+    let mut counterfeit_cards = Vec::new();
+    if has_outs {
+        // Same order as the outs of the high hand
+        unknown_cards.sort_by_key(|card| (card.value, card.suit.to_char()));
+        let mut board: Vec<Card> = community.cards().to_vec();
+        for &card in &unknown_cards {
+            board.push(card);
+            let low = best_low(hand.cards(), &board);
+            if low.is_some() && current.is_none_or(|current| low < Some(current)) {
+                outs.push(card);
+            }
+            if has_nut_low && low != nut_low(&board) {
+                counterfeit_cards.push(card);
+            }
+            board.pop();
+        }
+    }
+    let counterfeit_prob = if unknown_cards.is_empty() {
+        0.0
+    } else {
+        counterfeit_cards.len() as f64 * 100.0 / unknown_cards.len() as f64
+    };
+
+    LowReport {
+        current,
+        nut_low: nut_low(community.cards()),
+        possible_prob: num_possible as f64 * 100.0 / num_runouts as f64,
+        exact_prob: num_lows as f64 * 100.0 / num_runouts as f64,
+        outs,
+        counterfeit_cards,
+        counterfeit_prob,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_prob::{get_unknown_cards, parse_cards};
+
+    fn low(hole: &str, board: &str) -> Option<LowHand> {
+        best_low(
+            parse_cards(hole).unwrap().cards(),
+            parse_cards(board).unwrap().cards(),
+        )
+    }
+
+    #[test]
+    fn test_best_low() {
+        assert_eq!(low("Ah2dKsKc", "3c4h5d"), Some(LowHand([5, 4, 3, 2, 1])));
+        assert_eq!(
+            low("Ah2dKsKc", "3c7h8dQs").map(|low| low.to_string()),
+            Some("8-7-3-2-A".to_string())
+        );
+        // The 9 is too high, and 2 board cards can't make a low
+        assert_eq!(low("Ah2dKsKc", "3c4h9d"), None);
+        assert_eq!(low("Ah2dKsKc", "3c4hKd"), None);
+        // Only 2 hole cards play, the third low hole card doesn't help
+        assert_eq!(low("Ah2d3sKc", "4c8h8dKs"), None);
+    }
+
+    // All 3 low board cards play, the nut low holds the A-3
+    #[test]
+    fn test_nut_low() {
+        let board = parse_cards("2c5h8dKs").unwrap();
+        assert_eq!(nut_low(board.cards()), Some(LowHand([8, 5, 3, 2, 1])));
+        let board = parse_cards("2c5hKd").unwrap();
+        assert_eq!(nut_low(board.cards()), None);
+    }
+
+    // A-2 with a 3-4 on the board: any 5 to 8 makes a low, there is no low to counterfeit yet
+    #[test]
+    fn test_low_outs() {
+        let hand = parse_cards("Ah2dKsKc").unwrap();
+        let board = parse_cards("3c4hQd").unwrap();
+        let deck = get_unknown_cards(Game::OmahaHiLo, &hand, &board, &[]).unwrap();
+        let low_report = calc_low(Game::OmahaHiLo, false, &deck, &hand, &board, true);
+        assert_eq!(low_report.current, None);
+        assert_eq!(low_report.outs.len(), 16);
+        assert!(low_report.counterfeit_cards.is_empty());
+        assert!((low_report.exact_prob - 16.0 * 100.0 / 45.0).abs() < 1e-9);
+    }
+
+    // A-2 with the nut low on the turn: an ace or a 2 on the river counterfeits it
+    #[test]
+    fn test_counterfeit() {
+        let hand = parse_cards("Ah2dKsKc").unwrap();
+        let board = parse_cards("3c4h8dQs").unwrap();
+        let deck = get_unknown_cards(Game::OmahaHiLo, &hand, &board, &[]).unwrap();
+        let low_report = calc_low(Game::OmahaHiLo, false, &deck, &hand, &board, true);
+        assert_eq!(low_report.current, Some(LowHand([8, 4, 3, 2, 1])));
+        assert_eq!(low_report.nut_low, Some(LowHand([8, 4, 3, 2, 1])));
+        assert!(low_report.is_nut());
+        // A 5, 6 or 7 makes a better low
+        assert_eq!(low_report.outs.len(), 12);
+        assert_eq!(low_report.counterfeit_cards.len(), 6);
+        assert!((low_report.counterfeit_prob - 6.0 * 100.0 / 44.0).abs() < 1e-9);
+    }
+}
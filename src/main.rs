@@ -1,14 +1,70 @@
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg};
 use colored::*;
-use rs_poker::core::{Deck, Hand};
-use std::collections::HashMap;
-mod calc_prob;
+use poker_prob::batch::{self, BatchFormat};
+use poker_prob::hand_history;
+use poker_prob::pot_odds;
+use poker_prob::repl::{self, Command, Session};
+use poker_prob::report;
+use poker_prob::server;
+use poker_prob::{
+    analyze, parse_cards, AnalyzeOptions, DiscardOption, Game, HandRank, JsonReport, LowReport,
+    PokerProbError, PreflopReport, Range, Report, Street,
+};
+use rs_poker::core::{Card, Rankable};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process;
 
-fn get_cli_args() -> (String, String, bool) {
-    let matches = App::new("gsheet_writer")
+struct CliArgs {
+    game: Game,
+    my_hand: String,
+    community_cards: String,
+    dead_cards: String,
+    draw_target: Option<HandRank>,
+    all_in: bool,
+    villains: Vec<String>,
+    iterations: u32,
+    seed: Option<u64>,
+    pot: Option<f64>,
+    bet: Option<f64>,
+    effective_stack: Option<f64>,
+    format: OutputFormat,
+    batch: Option<BatchArgs>,
+    repl: Option<ReplArgs>,
+    history: Option<HistoryArgs>,
+    serve_port: Option<u16>,
+}
+
+struct ReplArgs {
+    game: Game,
+    my_hand: Option<String>,
+}
+
+struct HistoryArgs {
+    paths: Vec<String>,
+    output: Option<String>,
+    output_format: BatchFormat,
+}
+
+struct BatchArgs {
+    input: String,
+    input_format: Option<BatchFormat>,
+    output: Option<String>,
+    output_format: Option<BatchFormat>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+fn get_cli_args() -> CliArgs {
+    let matches = App::new("poker_prob")
         .version("0.1")
         .author("eRaMvn")
-        .about("CLI to write to google sheet given ranges and values")
+        .about("Outs, probabilities and equity of a poker hand, street by street")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::new("my-hand")
                 .long("mh")
@@ -21,8 +77,35 @@ fn get_cli_args() -> (String, String, bool) {
             Arg::new("community-cards")
                 .long("ch")
                 .value_name("STRING")
-                .help("Set community cards")
-                .required(true)
+                .help("Set community cards, leave out preflop")
+                .default_value("")
+                .hide_default_value(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("dead-cards")
+                .long("dead")
+                .value_name("STRING")
+                .help("Set cards that can't come anymore, e.g. the upcards of the other players in stud")
+                .default_value("")
+                .hide_default_value(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("game")
+                .long("game")
+                .value_name("GAME")
+                .help("Set the game, omaha and omahahilo are played with 4 to 6 hole cards, shortdeck with 36 cards, stud with our 3 to 7 down and up cards as the hand and draw with 5 cards")
+                .possible_values(["holdem", "omaha", "omahahilo", "shortdeck", "stud", "draw"])
+                .default_value("holdem")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("target")
+                .long("target")
+                .value_name("HAND_RANK")
+                .help("Set the hand rank to aim for with the discards in draw, e.g. flush, the one above the made hand by default")
+                .validator(|value| value.parse::<HandRank>())
                 .takes_value(true),
         )
         .arg(
@@ -31,42 +114,722 @@ fn get_cli_args() -> (String, String, bool) {
                 .help("Set whether this is all in or not")
                 .takes_value(false),
         )
+        .arg(
+            Arg::new("villain")
+                .long("villain")
+                .value_name("STRING")
+                .help("Set the hand or range of an opponent (e.g. AhKd or TT+,AKs,KQo:0.5), can be used multiple times")
+                .multiple_occurrences(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("iterations")
+                .long("iterations")
+                .value_name("NUMBER")
+                .help("Set the number of runouts to simulate for equity")
+                .default_value("10000")
+                .validator(|value| value.parse::<u32>())
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("NUMBER")
+                .help("Set the seed of the equity simulation to reproduce results")
+                .validator(|value| value.parse::<u64>())
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("pot")
+                .long("pot")
+                .value_name("NUMBER")
+                .help("Set the size of the pot, including the bet to call")
+                .requires("bet")
+                .validator(|value| value.parse::<f64>())
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("bet")
+                .long("bet")
+                .value_name("NUMBER")
+                .help("Set the size of the bet to call")
+                .requires("pot")
+                .validator(|value| value.parse::<f64>())
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("effective-stack")
+                .long("effective-stack")
+                .value_name("NUMBER")
+                .help("Set the effective stack before calling, for implied odds")
+                .requires("bet")
+                .validator(|value| value.parse::<f64>())
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .help("Set the output format, json prints the report for scripts")
+                .possible_values(["text", "json"])
+                .default_value("text")
+                .takes_value(true),
+        )
+        .subcommand(
+            App::new("batch")
+                .about("Analyze every scenario of a CSV or NDJSON file and write one result row for each")
+                .arg(
+                    Arg::new("input")
+                        .value_name("FILE")
+                        .help("Set the file of scenarios, - reads from stdin")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("input-format")
+                        .long("input-format")
+                        .value_name("FORMAT")
+                        .help("Set the format of the scenarios, guessed from the file extension by default")
+                        .possible_values(["csv", "ndjson"])
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Set the file to write the results to, stdout by default")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Set the format of the results, the format of the scenarios by default")
+                        .possible_values(["csv", "ndjson"])
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new("history")
+                .about("Replay PokerStars hand histories, writing the outs of hero on each flop and turn")
+                .arg(
+                    Arg::new("paths")
+                        .value_name("PATH")
+                        .help("Set the hand history files, or folders of .txt files")
+                        .required(true)
+                        .multiple_values(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Set the file to write the results to, stdout by default")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Set the format of the results")
+                        .possible_values(["csv", "ndjson"])
+                        .default_value("csv")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new("serve")
+                .about("Answer POST /outs and POST /equity with the JSON report, on localhost only")
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("NUMBER")
+                        .help("Set the port to listen on")
+                        .validator(|value| value.parse::<u16>())
+                        .default_value("7878")
+                        .takes_value(true),
+                ),
+        )
+        .subcommand(
+            App::new("repl")
+                .about("Follow a hand street by street, adding the cards as they come")
+                .arg(
+                    Arg::new("my-hand")
+                        .long("mh")
+                        .value_name("STRING")
+                        .help("Set my hand, can also be set later with the hand command")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::new("game")
+                        .long("game")
+                        .value_name("GAME")
+                        .help("Set the game, omaha and omahahilo are played with 4 to 6 hole cards and shortdeck with 36 cards")
+                        .possible_values(["holdem", "omaha", "omahahilo", "shortdeck"])
+                        .default_value("holdem")
+                        .takes_value(true),
+                ),
+        )
         .get_matches();
 
-    (
-        matches.value_of("my-hand").unwrap().to_string(),
-        matches.value_of("community-cards").unwrap().to_string(),
-        matches.is_present("all-in"),
-    )
+    let game = |game: Option<&str>| match game {
+        Some("omaha") => Game::Omaha,
+        Some("omahahilo") => Game::OmahaHiLo,
+        Some("shortdeck") => Game::ShortDeck,
+        Some("stud") => Game::Stud,
+        Some("draw") => Game::Draw,
+        _ => Game::Holdem,
+    };
+    let repl = matches
+        .subcommand_matches("repl")
+        .map(|repl_matches| ReplArgs {
+            game: game(repl_matches.value_of("game")),
+            my_hand: repl_matches
+                .value_of("my-hand")
+                .map(|my_hand| my_hand.to_string()),
+        });
+    let batch_format = |format: &str| match format {
+        "csv" => BatchFormat::Csv,
+        _ => BatchFormat::Ndjson,
+    };
+    let batch = matches
+        .subcommand_matches("batch")
+        .map(|batch_matches| BatchArgs {
+            input: batch_matches.value_of("input").unwrap().to_string(),
+            input_format: batch_matches.value_of("input-format").map(batch_format),
+            output: batch_matches
+                .value_of("output")
+                .map(|output| output.to_string()),
+            output_format: batch_matches.value_of("format").map(batch_format),
+        });
+    let history = matches
+        .subcommand_matches("history")
+        .map(|history_matches| HistoryArgs {
+            paths: history_matches
+                .values_of("paths")
+                .unwrap()
+                .map(|path| path.to_string())
+                .collect(),
+            output: history_matches
+                .value_of("output")
+                .map(|output| output.to_string()),
+            output_format: batch_format(history_matches.value_of("format").unwrap()),
+        });
+    // The iterations and seed can be given before or after the subcommand
+    let settings_matches = matches
+        .subcommand()
+        .map(|(_, subcommand_matches)| subcommand_matches)
+        .unwrap_or(&matches);
+
+    CliArgs {
+        game: game(matches.value_of("game")),
+        my_hand: matches.value_of("my-hand").unwrap_or_default().to_string(),
+        community_cards: matches
+            .value_of("community-cards")
+            .unwrap_or_default()
+            .to_string(),
+        dead_cards: matches
+            .value_of("dead-cards")
+            .unwrap_or_default()
+            .to_string(),
+        draw_target: matches
+            .value_of("target")
+            .map(|target| target.parse().expect("Target should be a hand rank.")),
+        all_in: matches.is_present("all-in"),
+        villains: matches
+            .values_of("villain")
+            .map(|values| values.map(|value| value.to_string()).collect())
+            .unwrap_or_default(),
+        iterations: settings_matches
+            .value_of("iterations")
+            .unwrap()
+            .parse()
+            .expect("Iterations should be a number."),
+        seed: settings_matches
+            .value_of("seed")
+            .map(|seed| seed.parse().expect("Seed should be a number.")),
+        pot: matches
+            .value_of("pot")
+            .map(|pot| pot.parse().expect("Pot should be a number.")),
+        bet: matches
+            .value_of("bet")
+            .map(|bet| bet.parse().expect("Bet should be a number.")),
+        effective_stack: matches
+            .value_of("effective-stack")
+            .map(|stack| stack.parse().expect("Effective stack should be a number.")),
+        format: match matches.value_of("format") {
+            Some("json") => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        },
+        batch,
+        repl,
+        history,
+        serve_port: matches.subcommand_matches("serve").map(|serve_matches| {
+            serve_matches
+                .value_of("port")
+                .unwrap()
+                .parse()
+                .expect("Port should be a number.")
+        }),
+    }
 }
 
 fn main() {
-    let (my_cards_arg, community_cards_arg, all_in) = get_cli_args();
-    let my_cards =
-        Hand::new_from_str(my_cards_arg.as_str()).expect("Should be able to create a hand.");
-    let community_cards =
-        Hand::new_from_str(community_cards_arg.as_str()).expect("Should be able to create a hand.");
-    let deck: Deck = calc_prob::get_unknown_cards(&my_cards, &community_cards);
-
-    let ranks_to_check = HashMap::from([
-        ("One Pair", calc_prob::HandRank::OnePair),
-        ("Two Pair", calc_prob::HandRank::TwoPair),
-        ("Three Of A Kind", calc_prob::HandRank::ThreeOfAKind),
-        ("Straight", calc_prob::HandRank::Straight),
-        ("Flush", calc_prob::HandRank::Flush),
-        ("Full House", calc_prob::HandRank::FullHouse),
-    ]);
-
-    let mut outs: i8;
-    let mut string_to_print: String;
-    let mut four_and_two_prob: i8;
+    let cli_args = get_cli_args();
+    if let Err(err) = run(&cli_args) {
+        eprintln!("{}", err.to_string().red());
+        process::exit(err.exit_code());
+    }
+}
+
+fn run(cli_args: &CliArgs) -> Result<(), PokerProbError> {
+    // Numbers out of their range exit with their own code, clap only checks that they are numbers
+    report::check_iterations(cli_args.iterations)?;
+    pot_odds::check_sizes(cli_args.pot, cli_args.bet, cli_args.effective_stack)?;
+    if let Some(batch_args) = &cli_args.batch {
+        return run_batch(batch_args, cli_args);
+    }
+    if let Some(repl_args) = &cli_args.repl {
+        return run_repl(repl_args);
+    }
+    if let Some(history_args) = &cli_args.history {
+        return run_history(history_args);
+    }
+    if let Some(port) = cli_args.serve_port {
+        println!("Listening on http://127.0.0.1:{}", port);
+        return server::serve(port, cli_args.iterations);
+    }
+
+    let my_cards = parse_cards(cli_args.my_hand.as_str())?;
+    let community_cards = parse_cards(cli_args.community_cards.as_str())?;
+    let villains = cli_args
+        .villains
+        .iter()
+        .map(|villain| Range::parse(villain.as_str()).map_err(PokerProbError::InvalidRange))
+        .collect::<Result<_, _>>()?;
+    let options = AnalyzeOptions {
+        game: cli_args.game,
+        all_in: cli_args.all_in,
+        villains,
+        iterations: cli_args.iterations,
+        seed: cli_args.seed.unwrap_or_else(rand::random),
+        pot: cli_args.pot,
+        bet: cli_args.bet,
+        effective_stack: cli_args.effective_stack,
+        dead_cards: parse_cards(cli_args.dead_cards.as_str())?.cards().to_vec(),
+        draw_target: cli_args.draw_target,
+    };
+    let report = analyze(&my_cards, &community_cards, &options)?;
+
+    if cli_args.format == OutputFormat::Json {
+        let json_report = JsonReport::new(&report, &options);
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json_report).expect("Report should serialize to JSON.")
+        );
+        return Ok(());
+    }
+
+    print_street(&report, &options);
+
+    if !options.villains.is_empty() {
+        for (i, num_combos) in report.villain_combos.iter().enumerate() {
+            println!(
+                "Villain {} has {} combos after card removal",
+                i + 1,
+                num_combos
+            );
+        }
+        print_true_outs(&report, options.all_in);
+
+        if let Some(equity) = report.equity {
+            println!(
+                "Equity over {} runouts (seed {}): win {}, tie {}, lose {}",
+                options.iterations,
+                options.seed,
+                format!("{:.2}%", equity.win).green(),
+                format!("{:.2}%", equity.tie).normal(),
+                format!("{:.2}%", equity.lose).red()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// Function to follow a hand street by street, reading one command per line until quit or the end of the input
+fn run_repl(repl_args: &ReplArgs) -> Result<(), PokerProbError> {
+    let hand = match &repl_args.my_hand {
+        Some(my_hand) => {
+            let hand = parse_cards(my_hand)?;
+            repl_args.game.check_hole_cards(&hand)?;
+            hand.cards().to_vec()
+        }
+        None => Vec::new(),
+    };
+    let mut session = Session::new(repl_args.game, hand);
+    println!("Type help for the commands");
+    print_session(&session);
+
+    let stdin = io::stdin();
+    let mut line = String::new();
+    loop {
+        let prompt = match session.street() {
+            Some(street) => street.to_string().to_lowercase(),
+            None => format!("flop {}/3", session.board.len()),
+        };
+        print!("{}> ", prompt);
+        io::stdout()
+            .flush()
+            .map_err(|err| PokerProbError::Io(err.to_string()))?;
+
+        line.clear();
+        let num_bytes = stdin
+            .read_line(&mut line)
+            .map_err(|err| PokerProbError::Io(err.to_string()))?;
+        if num_bytes == 0 {
+            println!();
+            break;
+        }
+
+        let command = match repl::parse_command(&line) {
+            Ok(command) => command,
+            Err(err) => {
+                eprintln!("{}", err.red());
+                continue;
+            }
+        };
+        match command {
+            Command::Quit => break,
+            Command::Help => {
+                println!("{}", REPL_HELP);
+                continue;
+            }
+            _ => {}
+        }
+        match session.apply(&command) {
+            Ok(()) => print_session(&session),
+            Err(err) => eprintln!("{}", err.to_string().red()),
+        }
+    }
+    Ok(())
+}
+
+const REPL_HELP: &str = "Commands:
+    hand AhKh     Set the hole cards
+    board 7h6h2c  Add community cards, one at a time or the whole street
+    dead 2c       Add cards that can't come anymore, e.g. folded by other players
+    allin         Switch between dealing the next street and every remaining card
+    undo          Take back the last change
+    reset         Start a new hand, undo brings the last one back
+    show          Print the outs again
+    quit          Leave";
+
+fn print_session(session: &Session) {
+    let mut state = format!(
+        "Hand: {}, board: {}",
+        cards_to_string(&session.hand),
+        cards_to_string(&session.board)
+    );
+    if !session.dead_cards.is_empty() {
+        state += &format!(", dead: {}", cards_to_string(&session.dead_cards));
+    }
+    if session.all_in {
+        state += ", all in";
+    }
+    println!("{}", state);
+
+    if session.hand.is_empty() {
+        // The REPL only plays the games with a flop
+        let example = match session.game {
+            Game::Holdem | Game::ShortDeck => "AhKh",
+            _ => "AhKhQd2c",
+        };
+        println!("Set the hole cards with: hand {}", example);
+        return;
+    }
+    if session.street().is_none() {
+        println!(
+            "Waiting for the rest of the flop ({}/3)",
+            session.board.len()
+        );
+        return;
+    }
+    match session.analyze() {
+        Ok(report) => print_street(
+            &report,
+            &AnalyzeOptions {
+                game: session.game,
+                all_in: session.all_in,
+                ..AnalyzeOptions::default()
+            },
+        ),
+        Err(err) => eprintln!("{}", err.to_string().red()),
+    }
+}
+
+// Function to analyze every scenario of the input file and write the result rows
+fn run_batch(batch_args: &BatchArgs, cli_args: &CliArgs) -> Result<(), PokerProbError> {
+    let io_error = |err: io::Error| PokerProbError::Io(format!("{}: {}", batch_args.input, err));
+    let input_format = batch_args
+        .input_format
+        .or_else(|| BatchFormat::from_path(&batch_args.input))
+        .ok_or_else(|| {
+            PokerProbError::Io(format!(
+                "Can't tell the format of {}, set --input-format",
+                batch_args.input
+            ))
+        })?;
+    let output_format = batch_args.output_format.unwrap_or(input_format);
+
+    let reader: Box<dyn Read> = if batch_args.input == "-" {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(&batch_args.input).map_err(io_error)?)
+    };
+    let writer: Box<dyn Write> = match &batch_args.output {
+        Some(output) => Box::new(
+            File::create(output)
+                .map_err(|err| PokerProbError::Io(format!("{}: {}", output, err)))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+
+    let num_rows = batch::run_batch(
+        reader,
+        writer,
+        input_format,
+        output_format,
+        cli_args.iterations,
+        cli_args.seed.unwrap_or_else(rand::random),
+    )?;
+    if let Some(output) = &batch_args.output {
+        println!("Wrote {} results to {}", num_rows, output);
+    }
+    Ok(())
+}
+
+// Function to read every hand history file, a folder is read file by file
+fn read_hand_history_files(path: &str) -> Result<Vec<String>, PokerProbError> {
+    let io_error = |err: io::Error| PokerProbError::Io(format!("{}: {}", path, err));
+    let metadata = std::fs::metadata(path).map_err(io_error)?;
+    let mut file_paths = if metadata.is_dir() {
+        std::fs::read_dir(path)
+            .map_err(io_error)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error)?
+            .into_iter()
+            .filter(|file_path| file_path.extension().is_some_and(|ext| ext == "txt"))
+            .collect()
+    } else {
+        vec![std::path::PathBuf::from(path)]
+    };
+    file_paths.sort();
+
+    file_paths
+        .iter()
+        .map(|file_path| {
+            // Old hand histories are not always valid UTF-8
+            std::fs::read(file_path)
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .map_err(|err| PokerProbError::Io(format!("{}: {}", file_path.display(), err)))
+        })
+        .collect()
+}
+
+fn run_history(history_args: &HistoryArgs) -> Result<(), PokerProbError> {
+    let mut histories = Vec::new();
+    for path in &history_args.paths {
+        for text in read_hand_history_files(path)? {
+            histories.extend(hand_history::parse_hand_histories(&text));
+        }
+    }
+
+    let writer: Box<dyn Write> = match &history_args.output {
+        Some(output) => Box::new(
+            File::create(output)
+                .map_err(|err| PokerProbError::Io(format!("{}: {}", output, err)))?,
+        ),
+        None => Box::new(io::stdout()),
+    };
+    let num_rows = hand_history::run_replay(&histories, writer, history_args.output_format)?;
+    if let Some(output) = &history_args.output {
+        println!(
+            "Wrote {} results for {} hands to {}",
+            num_rows,
+            histories.len(),
+            output
+        );
+    }
+    Ok(())
+}
+
+// Function to print what is known on this street, the outs only exist while a card is still to come
+fn print_street(report: &Report, options: &AnalyzeOptions) {
+    match report.street {
+        Street::Preflop => {
+            if options.all_in {
+                println!("Preflop, probability of finishing with each hand by the river:");
+            } else {
+                println!("Preflop, probability of flopping each hand:");
+            }
+            for hand_rank_report in report.hand_ranks.iter().rev() {
+                if hand_rank_report.exact_prob > 0.0 {
+                    println!(
+                        "    {}: {:.2}%",
+                        hand_rank_report.hand_rank, hand_rank_report.exact_prob
+                    );
+                }
+            }
+            if let Some(preflop) = &report.preflop {
+                print_preflop(preflop);
+            }
+        }
+        Street::River | Street::SeventhStreet => print_made_hand(report),
+        Street::Flop
+        | Street::Turn
+        | Street::ThirdStreet
+        | Street::FourthStreet
+        | Street::FifthStreet
+        | Street::SixthStreet => {
+            print_made_hand(report);
+            print_outs(report, options);
+        }
+        Street::Draw => print_draw(report),
+    }
+    if let Some(low) = &report.low {
+        print_low(low, report.street);
+    }
+}
+
+// Function to print the made hand with its 5 cards, the cards making the hand rank come first
+fn print_made_hand(report: &Report) {
+    let made_hand = &report.made_hand;
+    let mut made_hand_string = format!(
+        "{}, made hand: {} ({})",
+        report.street,
+        made_hand.hand_rank,
+        cards_to_string(&made_hand.cards)
+    );
+    if !made_hand.kickers.is_empty() {
+        made_hand_string += &format!(", kickers: {}", cards_to_string(&made_hand.kickers));
+    }
+    println!("{}", made_hand_string);
+}
+
+// Function to print the strength of the starting hand and its equity against random hands
+fn print_preflop(preflop: &PreflopReport) {
+    println!(
+        "{}: Chen formula {} points, Sklansky-Malmuth group {}",
+        preflop.starting_hand, preflop.chen_score, preflop.sklansky_group
+    );
+    println!("All-in equity against random hands:");
+    for (i, equity) in preflop.equity_vs_random.iter().enumerate() {
+        println!(
+            "    {} {}: win {}, tie {:.2}%",
+            i + 1,
+            if i == 0 { "hand" } else { "hands" },
+            format!("{:.2}%", equity.win).green(),
+            equity.tie
+        );
+    }
+}
+
+// Function to print the low half of the pot next to the high hand
+fn print_low(low: &LowReport, street: Street) {
+    match (low.current, low.nut_low) {
+        (Some(current), _) if low.is_nut() => {
+            println!("Low: {} {}", current, "(nut low)".green())
+        }
+        (Some(current), Some(nut_low)) => println!("Low: {}, the nut low is {}", current, nut_low),
+        _ => println!("Low: {}", "no qualifying low".red()),
+    }
+    println!(
+        "A low is possible on {:.2}% of the boards, we finish with a low {:.2}% of the time",
+        low.possible_prob, low.exact_prob
+    );
+    if !street.has_outs() {
+        return;
+    }
+    println!(
+        "    Low outs: {} outs: {}",
+        low.outs.len(),
+        cards_to_string(&low.outs)
+    );
+    if !low.counterfeit_cards.is_empty() {
+        println!(
+            "    Counterfeit risk {}: {}",
+            format!("{:.2}%", low.counterfeit_prob).red(),
+            cards_to_string(&low.counterfeit_cards)
+        );
+    }
+}
+
+// Number of discard options printed, all of them are in the JSON report
+const NUM_DISCARD_OPTIONS_PRINTED: usize = 5;
+
+// Function to print the best discard options and what the best one makes after the draw
+fn print_draw(report: &Report) {
+    print_made_hand(report);
+    let draw = match &report.draw {
+        Some(draw) => draw,
+        None => return,
+    };
+
+    let describe = |option: &DiscardOption| {
+        if option.keep.is_empty() {
+            format!("Draw {}", option.discard.len())
+        } else {
+            format!(
+                "Keep {}, draw {}",
+                cards_to_string(&option.keep),
+                option.discard.len()
+            )
+        }
+    };
+    println!(
+        "Discard options by the probability of finishing with {} or better:",
+        draw.target
+    );
+    for option in draw.options.iter().take(NUM_DISCARD_OPTIONS_PRINTED) {
+        println!(
+            "    {}: {}",
+            describe(option),
+            format!("{:.2}%", option.target_prob).green()
+        );
+    }
+
+    if let Some(best) = draw.options.first() {
+        println!(
+            "{}, probability of finishing with each hand:",
+            describe(best)
+        );
+        for (hand_rank, prob) in best.exact_probs.iter().rev() {
+            println!("    {}: {:.2}%", hand_rank, prob);
+        }
+    }
+}
+
+fn cards_to_string(cards: &[Card]) -> String {
+    let card_strings: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+    card_strings.join(" ")
+}
+
+// Function to print the outs and the probabilities of each hand rank on the flop or the turn
+fn print_outs(report: &Report, options: &AnalyzeOptions) {
     let mut hand_name_colored: ColoredString;
     let mut prob_string_colored: ColoredString;
 
-    for (name, hand_rank) in ranks_to_check {
-        outs = hand_rank.calc_outs(&deck, &my_cards, &community_cards);
-        four_and_two_prob =
-            calc_prob::calc_4_and_2_probs(all_in, community_cards.len() as i8, outs);
+    // The hand ranks we have already made are in the made hand, only the better ones have outs
+    let current_strength = report.game.strength(report.current_hand_rank);
+    for hand_rank_report in report.hand_ranks.iter().filter(|hand_rank_report| {
+        report.game.strength(hand_rank_report.hand_rank) > current_strength
+    }) {
+        let name = hand_rank_report.hand_rank.to_string();
+        let four_and_two_prob = hand_rank_report.four_and_two_prob;
 
         if four_and_two_prob < 10 {
             hand_name_colored = name.red();
@@ -78,15 +841,110 @@ fn main() {
             hand_name_colored = name.normal();
             prob_string_colored = (four_and_two_prob.to_string() + "%").normal();
         }
-        string_to_print = format!(
-            "{} has the probability of {}",
-            hand_name_colored, prob_string_colored
+        println!(
+            "{} has the probability of {} (exact: {:.2}%)",
+            hand_name_colored, prob_string_colored, hand_rank_report.exact_prob
         );
-        println!("{}", string_to_print);
+
+        if !hand_rank_report.outs.is_empty() {
+            println!("    {}: {}", name, cards_to_string(&hand_rank_report.outs));
+        }
     }
 
-    // Sample usage
-    // let flush_outs = calc_prob::HandRank::Flush;
-    // println!("{:?}", flush_outs.calc_outs(&deck, &hand, &community));
-    // let some_card: Card = Card { value: (Value::King), suit: (Suit::Heart) };
+    let num_unique_outs: usize = report.combined_outs.values().map(|cards| cards.len()).sum();
+    println!(
+        "Improve on {} with {} unique outs, probability of {}% (exact: {:.2}%)",
+        report.current_hand_rank, num_unique_outs, report.improve_prob, report.exact_improve_prob
+    );
+
+    // Go from the best hand rank down, so the count of outs for this rank or better adds up
+    let mut num_outs_or_better = 0;
+    for (hand_rank, cards) in report.combined_outs_best_first() {
+        num_outs_or_better += cards.len();
+        println!(
+            "    {}: {} outs, {} to improve to {} or better: {}",
+            hand_rank,
+            cards.len(),
+            num_outs_or_better,
+            hand_rank,
+            cards_to_string(cards)
+        );
+    }
+
+    if let Some(pot_odds) = report.pot_odds {
+        let mut odds_string = format!(
+            "Calling {} into {} needs {:.2}% equity",
+            options.bet.unwrap_or_default(),
+            options.pot.unwrap_or_default(),
+            pot_odds.required_equity
+        );
+        if let Some(implied_required_equity) = pot_odds.implied_required_equity {
+            odds_string += &format!(" ({:.2}% with implied odds)", implied_required_equity);
+        }
+        println!("{}", odds_string);
+
+        let decision = if pot_odds.call {
+            "Call".green()
+        } else {
+            "Fold".red()
+        };
+        let mut decision_string = format!(
+            "{} with {:.2}% to improve (rule of 4 and 2: {}%), EV of calling is {:+.2}",
+            decision, report.exact_improve_prob, report.improve_prob, pot_odds.ev_of_calling
+        );
+        if let Some(implied_ev_of_calling) = pot_odds.implied_ev_of_calling {
+            decision_string += &format!(" ({:+.2} with implied odds)", implied_ev_of_calling);
+        }
+        println!("{}", decision_string);
+    }
+
+    if report.straight_values.is_empty() {
+        println!("{}", report.straight_draw);
+    } else {
+        let value_chars: Vec<String> = report
+            .straight_values
+            .iter()
+            .map(|value| value.to_char().to_string())
+            .collect();
+        println!(
+            "{}, completed by: {}",
+            report.straight_draw,
+            value_chars.join(" ")
+        );
+    }
+}
+
+// Function to print the outs checked against the hands of the opponents
+fn print_true_outs(report: &Report, all_in: bool) {
+    let true_outs = match &report.true_outs {
+        Some(true_outs) => true_outs,
+        None => return,
+    };
+    let true_outs_prob = poker_prob::calc_prob::calc_4_and_2_probs(
+        all_in,
+        report.board.len(),
+        true_outs.discounted_outs.round() as i32,
+    );
+    println!(
+        "True outs against villains: {} clean, {} dirty, {:.1} discounted outs, probability of {}% (next card: {:.2}%)",
+        true_outs.clean_outs.len(),
+        true_outs.dirty_outs.len(),
+        true_outs.discounted_outs,
+        true_outs_prob,
+        true_outs.discounted_outs * 100.0 / report.num_unknown_cards as f64
+    );
+    if !true_outs.clean_outs.is_empty() {
+        println!(
+            "    Clean: {}",
+            cards_to_string(&true_outs.clean_outs).green()
+        );
+    }
+    if !true_outs.dirty_outs.is_empty() {
+        let dirty_cards: Vec<String> = true_outs
+            .dirty_outs
+            .iter()
+            .map(|(card, share)| format!("{} ({:.0}%)", card, share * 100.0))
+            .collect();
+        println!("    Dirty: {}", dirty_cards.join(" ").red());
+    }
 }
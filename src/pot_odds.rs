@@ -0,0 +1,142 @@
+use crate::error::PokerProbError;
+use serde::Serialize;
+
+/// Call or fold decision given the price of the call.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PotOdds {
+    /// Equity needed for a call to break even, in percent.
+    pub required_equity: f64,
+    /// Equity needed when the rest of the effective stack is won as well when we hit, in percent.
+    pub implied_required_equity: Option<f64>,
+    /// Chips won or lost on average by calling.
+    pub ev_of_calling: f64,
+    /// Chips won or lost on average by calling, counting what is won behind when we hit.
+    pub implied_ev_of_calling: Option<f64>,
+    pub call: bool,
+}
+
+// Function to check the sizes before calculating the pot odds, a bet of 0 leaves nothing to call
+// and a negative pot or stack can't be played
+pub fn check_sizes(
+    pot: Option<f64>,
+    bet: Option<f64>,
+    effective_stack: Option<f64>,
+) -> Result<(), PokerProbError> {
+    if let Some(bet) = bet {
+        check_positive("Bet", bet)?;
+    }
+    if let Some(pot) = pot {
+        check_non_negative("Pot", pot)?;
+    }
+    if let Some(effective_stack) = effective_stack {
+        check_non_negative("Effective stack", effective_stack)?;
+    }
+    Ok(())
+}
+
+fn check_positive(name: &str, size: f64) -> Result<(), PokerProbError> {
+    // NaN fails every comparison, so it is rejected as well
+    if size > 0.0 && size.is_finite() {
+        Ok(())
+    } else {
+        Err(PokerProbError::InvalidOption(format!(
+            "{} should be more than 0, got {}",
+            name, size
+        )))
+    }
+}
+
+fn check_non_negative(name: &str, size: f64) -> Result<(), PokerProbError> {
+    if size >= 0.0 && size.is_finite() {
+        Ok(())
+    } else {
+        Err(PokerProbError::InvalidOption(format!(
+            "{} should be 0 or more, got {}",
+            name, size
+        )))
+    }
+}
+
+// Function to compare the equity (in percent) with the price of calling a bet.
+// The pot already holds the bet we have to call, the effective stack is counted before calling.
+pub fn calc_pot_odds(pot: f64, bet: f64, effective_stack: Option<f64>, equity: f64) -> PotOdds {
+    let win_share = equity / 100.0;
+    let required_equity = bet * 100.0 / (pot + bet);
+    let ev_of_calling = win_share * pot - (1.0 - win_share) * bet;
+
+    // Once we call, what is left of the stack can still be won on later streets
+    let behind = effective_stack.map(|stack| (stack - bet).max(0.0));
+    let implied_required_equity = behind.map(|behind| bet * 100.0 / (pot + bet + behind));
+    let implied_ev_of_calling =
+        behind.map(|behind| win_share * (pot + behind) - (1.0 - win_share) * bet);
+
+    let call = equity >= implied_required_equity.unwrap_or(required_equity);
+
+    PotOdds {
+        required_equity,
+        implied_required_equity,
+        ev_of_calling,
+        implied_ev_of_calling,
+        call,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Calling 20 into 100 needs 20 / 120 of the pot
+    #[test]
+    fn test_required_equity() {
+        let pot_odds = calc_pot_odds(100.0, 20.0, None, 10.0);
+        assert!((pot_odds.required_equity - 100.0 / 6.0).abs() < 1e-9);
+        assert_eq!(pot_odds.implied_required_equity, None);
+        assert!(!pot_odds.call);
+    }
+
+    // A flush draw with about 20% to hit is a call when the price is right
+    #[test]
+    fn test_call_with_enough_equity() {
+        let pot_odds = calc_pot_odds(100.0, 20.0, None, 19.57);
+        assert!(pot_odds.call);
+        assert!(pot_odds.ev_of_calling > 0.0);
+    }
+
+    #[test]
+    fn test_ev_of_calling() {
+        // 50% to win 100 and 50% to lose 50
+        let pot_odds = calc_pot_odds(100.0, 50.0, None, 50.0);
+        assert!((pot_odds.ev_of_calling - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_check_sizes() {
+        assert!(check_sizes(Some(0.0), Some(20.0), Some(0.0)).is_ok());
+        assert!(check_sizes(None, None, None).is_ok());
+        assert_eq!(
+            check_sizes(Some(0.0), Some(0.0), None),
+            Err(PokerProbError::InvalidOption(
+                "Bet should be more than 0, got 0".to_string()
+            ))
+        );
+        assert_eq!(
+            check_sizes(Some(-100.0), Some(20.0), None),
+            Err(PokerProbError::InvalidOption(
+                "Pot should be 0 or more, got -100".to_string()
+            ))
+        );
+        assert!(check_sizes(Some(100.0), Some(20.0), Some(-1.0)).is_err());
+        assert!(check_sizes(Some(f64::NAN), Some(20.0), None).is_err());
+        assert!(check_sizes(Some(100.0), Some(f64::INFINITY), None).is_err());
+    }
+
+    // Without enough direct odds, the chips behind can make it a call
+    #[test]
+    fn test_implied_odds() {
+        let pot_odds = calc_pot_odds(100.0, 50.0, Some(250.0), 20.0);
+        assert!(pot_odds.ev_of_calling < 0.0);
+        assert!((pot_odds.implied_required_equity.unwrap() - 100.0 / 7.0).abs() < 1e-9);
+        assert!(pot_odds.implied_ev_of_calling.unwrap() > 0.0);
+        assert!(pot_odds.call);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::equity::Equity;
+use rs_poker::core::{Card, Hand, Rankable, Value};
+
+/// Most random hands in the equity tables, a full table of 10 players.
+pub const MAX_RANDOM_OPPONENTS: usize = 9;
+
+// All-in equity of every starting hand against 1 to 9 random hands, regenerated with
+// `cargo run --release --example preflop_equity > src/preflop_equity.csv`
+const EQUITY_TABLE: &str = include_str!("preflop_equity.csv");
+
+// Sklansky-Malmuth groups from the best, the hands in none of them are in group 9
+const SKLANSKY_GROUPS: [&[&str]; 8] = [
+    &["AA", "KK", "QQ", "JJ", "AKs"],
+    &["TT", "AQs", "AJs", "KQs", "AKo"],
+    &["99", "JTs", "QJs", "KJs", "ATs", "AQo"],
+    &["T9s", "KQo", "88", "QTs", "98s", "J9s", "AJo", "KTs"],
+    &[
+        "77", "87s", "Q9s", "T8s", "KJo", "QJo", "JTo", "76s", "97s", "A9s", "A8s", "A7s", "A6s",
+        "A5s", "A4s", "A3s", "A2s", "65s",
+    ],
+    &[
+        "66", "ATo", "55", "86s", "KTo", "QTo", "54s", "K9s", "J8s", "75s",
+    ],
+    &[
+        "44", "J9o", "64s", "T9o", "53s", "33", "98o", "43s", "22", "K8s", "K7s", "K6s", "K5s",
+        "K4s", "K3s", "K2s", "T7s", "Q8s",
+    ],
+    &[
+        "87o", "A9o", "Q9o", "76o", "42s", "32s", "96s", "85s", "J8o", "J7s", "65o", "54o", "74s",
+        "K9o", "T8o",
+    ],
+];
+
+/// Strength of our 2 hole cards before the flop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreflopReport {
+    /// Starting hand such as `AKs`, `T9o` or `77`.
+    pub starting_hand: String,
+    /// Points of the Chen formula, from -1 for 72o up to 20 for aces.
+    pub chen_score: i32,
+    /// Sklansky-Malmuth group, from 1 for the best hands down to 9 for the hands in none of the groups.
+    pub sklansky_group: u8,
+    /// All-in equity against 1 to 9 random hands, the first one against a single hand.
+    pub equity_vs_random: Vec<Equity>,
+}
+
+#[derive(Debug)]
+struct EquityRow {
+    hand: String,
+    opponents: usize,
+    win: f64,
+    tie: f64,
+}
+
+// Function to read the rows of the equity tables, a plain `hand,opponents,win,tie` CSV without quotes
+// that is split by hand so the csv crate stays out of the WebAssembly build
+fn equity_rows() -> impl Iterator<Item = EquityRow> {
+    EQUITY_TABLE.lines().skip(1).map(|line| {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        let [hand, opponents, win, tie] = fields[..] else {
+            panic!("Preflop equity table should have 4 columns.");
+        };
+        EquityRow {
+            hand: hand.to_string(),
+            opponents: opponents
+                .parse()
+                .expect("Preflop equity table should be valid."),
+            win: win.parse().expect("Preflop equity table should be valid."),
+            tie: tie.parse().expect("Preflop equity table should be valid."),
+        }
+    })
+}
+
+// Function to name the starting hand of 2 cards, the highest value first, e.g. `AKs`, `T9o` or `77`
+pub fn starting_hand_name(first: Card, second: Card) -> String {
+    let (high, low) = if first.value >= second.value {
+        (first, second)
+    } else {
+        (second, first)
+    };
+    let suffix = if high.value == low.value {
+        ""
+    } else if high.suit == low.suit {
+        "s"
+    } else {
+        "o"
+    };
+    format!("{}{}{}", high.value.to_char(), low.value.to_char(), suffix)
+}
+
+// Points of the highest card in the Chen formula, half of the value below the jack
+fn chen_card_points(value: Value) -> f64 {
+    match value {
+        Value::Ace => 10.0,
+        Value::King => 8.0,
+        Value::Queen => 7.0,
+        Value::Jack => 6.0,
+        value => (value as u8 + 2) as f64 / 2.0,
+    }
+}
+
+// Function to score 2 hole cards with the Chen formula, half points are rounded up
+pub fn chen_score(first: Card, second: Card) -> i32 {
+    let (high, low) = if first.value >= second.value {
+        (first.value, second.value)
+    } else {
+        (second.value, first.value)
+    };
+
+    // Pairs count the card twice, at least 5 points
+    if high == low {
+        return (chen_card_points(high) * 2.0).max(5.0).ceil() as i32;
+    }
+
+    let mut score = chen_card_points(high);
+    if first.suit == second.suit {
+        score += 2.0;
+    }
+    let gap = high as u8 - low as u8 - 1;
+    score -= match gap {
+        0 => 0.0,
+        1 => 1.0,
+        2 => 2.0,
+        3 => 4.0,
+        _ => 5.0,
+    };
+    // Connected or one gap hands below a queen can make more straights
+    if gap <= 1 && high < Value::Queen {
+        score += 1.0;
+    }
+    score.ceil() as i32
+}
+
+// Function to find the Sklansky-Malmuth group of a starting hand
+pub fn sklansky_group(starting_hand: &str) -> u8 {
+    SKLANSKY_GROUPS
+        .iter()
+        .position(|group| group.contains(&starting_hand))
+        .map_or(9, |i| i as u8 + 1)
+}
+
+// Function to read the all-in equity of a starting hand against 1 to 9 random hands from the tables
+pub fn equity_vs_random(starting_hand: &str) -> Vec<Equity> {
+    let mut rows: Vec<EquityRow> = equity_rows()
+        .filter(|row| row.hand == starting_hand)
+        .collect();
+    rows.sort_by_key(|row| row.opponents);
+    rows.into_iter()
+        .map(|row| Equity {
+            win: row.win,
+            tie: row.tie,
+            lose: 100.0 - row.win - row.tie,
+        })
+        .collect()
+}
+
+// Function to score our 2 hole cards before the flop, with the equity against random hands
+pub fn calc_preflop(hand: &Hand) -> Option<PreflopReport> {
+    let [first, second] = hand.cards() else {
+        return None;
+    };
+    let starting_hand = starting_hand_name(*first, *second);
+    Some(PreflopReport {
+        chen_score: chen_score(*first, *second),
+        sklansky_group: sklansky_group(&starting_hand),
+        equity_vs_random: equity_vs_random(&starting_hand),
+        starting_hand,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc_prob::parse_cards;
+
+    fn chen(cards: &str) -> i32 {
+        let hand = parse_cards(cards).unwrap();
+        chen_score(hand.cards()[0], hand.cards()[1])
+    }
+
+    #[test]
+    fn test_chen_score() {
+        assert_eq!(chen("AhAd"), 20);
+        assert_eq!(chen("AhKh"), 12);
+        assert_eq!(chen("JhTh"), 9);
+        assert_eq!(chen("5h5d"), 5);
+        assert_eq!(chen("2h2d"), 5);
+        // 3.5 for the 7, 5 less for the gap, rounded up
+        assert_eq!(chen("7h2d"), -1);
+        assert_eq!(chen("Kd9c"), 4);
+    }
+
+    #[test]
+    fn test_starting_hand_name() {
+        let hand = parse_cards("9hTh").unwrap();
+        assert_eq!(starting_hand_name(hand.cards()[0], hand.cards()[1]), "T9s");
+        let hand = parse_cards("AdKh").unwrap();
+        assert_eq!(starting_hand_name(hand.cards()[0], hand.cards()[1]), "AKo");
+    }
+
+    #[test]
+    fn test_sklansky_group() {
+        assert_eq!(sklansky_group("AKs"), 1);
+        assert_eq!(sklansky_group("A5s"), 5);
+        assert_eq!(sklansky_group("K9o"), 8);
+        assert_eq!(sklansky_group("72o"), 9);
+    }
+
+    // Every starting hand has its 9 rows, and the equity goes down with more opponents
+    #[test]
+    fn test_equity_table() {
+        let num_rows = equity_rows().count();
+        assert_eq!(num_rows, 169 * MAX_RANDOM_OPPONENTS);
+
+        let equities = equity_vs_random("AA");
+        assert_eq!(equities.len(), MAX_RANDOM_OPPONENTS);
+        assert!(equities[0].win > 80.0);
+        assert!(equities.windows(2).all(|pair| pair[0].win > pair[1].win));
+
+        let preflop = calc_preflop(&parse_cards("2d7h").unwrap()).unwrap();
+        assert_eq!(preflop.starting_hand, "72o");
+        assert!(preflop.equity_vs_random[0].win < 35.0);
+    }
+}
@@ -0,0 +1,303 @@
+use rs_poker::core::{Card, Hand, Rankable, Suit, Value};
+
+/// Two hole cards and how often they are played in the range, from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightedCombo {
+    pub cards: [Card; 2],
+    pub weight: f64,
+}
+
+/// A hand range like `TT+,AKs,A2s-A5s,KQo:0.5` expanded to concrete two card combos.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Range {
+    pub combos: Vec<WeightedCombo>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Suitedness {
+    Suited,
+    OffSuit,
+    Any,
+}
+
+impl Range {
+    /// Parse a comma separated range. Every entry can end with `:weight` to only play it part of the time.
+    pub fn parse(range_str: &str) -> Result<Range, String> {
+        let mut range = Range::default();
+
+        for entry in range_str.split(',') {
+            let entry = entry.trim();
+            if entry.is_empty() {
+                continue;
+            }
+
+            let (hands_str, weight) = match entry.split_once(':') {
+                Some((hands_str, weight_str)) => {
+                    let weight: f64 = weight_str
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("Invalid weight in range entry '{}'", entry))?;
+                    if !(weight > 0.0 && weight <= 1.0) {
+                        return Err(format!(
+                            "Weight in range entry '{}' should be between 0 and 1",
+                            entry
+                        ));
+                    }
+                    (hands_str.trim(), weight)
+                }
+                None => (entry, 1.0),
+            };
+
+            for cards in parse_entry(hands_str)? {
+                range.add(cards, weight);
+            }
+        }
+
+        if range.combos.is_empty() {
+            return Err(format!("Range '{}' does not contain any hand", range_str));
+        }
+        Ok(range)
+    }
+
+    // Add a combo to the range, a combo listed twice keeps the last weight
+    fn add(&mut self, cards: [Card; 2], weight: f64) {
+        match self
+            .combos
+            .iter_mut()
+            .find(|combo| same_cards(&combo.cards, &cards))
+        {
+            Some(combo) => combo.weight = weight,
+            None => self.combos.push(WeightedCombo { cards, weight }),
+        }
+    }
+
+    /// Card removal: drop every combo holding one of the given cards.
+    pub fn remove_cards(&self, dead_cards: &[Card]) -> Range {
+        Range {
+            combos: self
+                .combos
+                .iter()
+                .filter(|combo| !combo.cards.iter().any(|card| dead_cards.contains(card)))
+                .copied()
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.combos.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.combos.is_empty()
+    }
+}
+
+fn same_cards(first: &[Card; 2], second: &[Card; 2]) -> bool {
+    (first[0] == second[0] && first[1] == second[1])
+        || (first[0] == second[1] && first[1] == second[0])
+}
+
+// Parse one entry of the range without its weight, e.g. `AKs`, `TT+`, `A2s-A5s`, `22-66` or `AhKd`
+fn parse_entry(entry: &str) -> Result<Vec<[Card; 2]>, String> {
+    let chars: Vec<char> = entry.chars().collect();
+
+    // A specific combo such as AhKd
+    if chars.len() == 4
+        && Suit::from_char(chars[1]).is_some()
+        && Suit::from_char(chars[3]).is_some()
+    {
+        let hand =
+            Hand::new_from_str(entry).map_err(|_| format!("Invalid hand '{}' in range", entry))?;
+        // The parsed hand does not keep the order of the cards, put the highest card first
+        let mut cards = [hand.cards()[0], hand.cards()[1]];
+        cards.sort_by(|a, b| b.cmp(a));
+        return Ok(vec![cards]);
+    }
+
+    if let Some((start_str, end_str)) = entry.split_once('-') {
+        let (start_high, start_low, start_suitedness) = parse_base(start_str.trim())?;
+        let (end_high, end_low, end_suitedness) = parse_base(end_str.trim())?;
+        if start_suitedness != end_suitedness {
+            return Err(format!(
+                "Both ends of '{}' should have the same suits",
+                entry
+            ));
+        }
+
+        let mut combos = Vec::new();
+        if start_high == start_low && end_high == end_low {
+            // Pairs, e.g. 22-66
+            for value in values_between(start_high, end_high) {
+                combos.extend(expand(value, value, start_suitedness));
+            }
+        } else if start_high == end_high && start_high != start_low && end_high != end_low {
+            // Same high card with a range of kickers, e.g. A2s-A5s
+            for low in values_between(start_low, end_low) {
+                combos.extend(expand(start_high, low, start_suitedness));
+            }
+        } else {
+            return Err(format!("Invalid range '{}'", entry));
+        }
+        return Ok(combos);
+    }
+
+    if let Some(base_str) = entry.strip_suffix('+') {
+        let (high, low, suitedness) = parse_base(base_str)?;
+        let mut combos = Vec::new();
+        if high == low {
+            // All the pairs from this one up to aces, e.g. TT+
+            for value in values_between(high, Value::Ace) {
+                combos.extend(expand(value, value, suitedness));
+            }
+        } else {
+            // All the kickers from this one up to the card below the high card, e.g. ATs+
+            for low in values_between(low, Value::from_u8(high as u8 - 1)) {
+                combos.extend(expand(high, low, suitedness));
+            }
+        }
+        return Ok(combos);
+    }
+
+    let (high, low, suitedness) = parse_base(entry)?;
+    Ok(expand(high, low, suitedness))
+}
+
+// Parse two values with an optional `s` or `o`, the highest value is returned first
+fn parse_base(base: &str) -> Result<(Value, Value, Suitedness), String> {
+    let chars: Vec<char> = base.chars().collect();
+    if chars.len() < 2 || chars.len() > 3 {
+        return Err(format!("Invalid hand '{}' in range", base));
+    }
+
+    let first = Value::from_char(chars[0]).ok_or(format!("Invalid card value in '{}'", base))?;
+    let second = Value::from_char(chars[1]).ok_or(format!("Invalid card value in '{}'", base))?;
+    let suitedness = match chars.get(2) {
+        None => Suitedness::Any,
+        Some('s') | Some('S') => Suitedness::Suited,
+        Some('o') | Some('O') => Suitedness::OffSuit,
+        Some(_) => return Err(format!("Invalid suitedness in '{}'", base)),
+    };
+
+    if first == second && suitedness == Suitedness::Suited {
+        return Err(format!("Pairs can not be suited in '{}'", base));
+    }
+
+    Ok((first.max(second), first.min(second), suitedness))
+}
+
+// All the values between two values, both included, in any order
+fn values_between(first: Value, second: Value) -> Vec<Value> {
+    let low = first.min(second) as u8;
+    let high = first.max(second) as u8;
+    (low..=high).map(Value::from_u8).collect()
+}
+
+// Every combination of suits for the two values
+fn expand(high: Value, low: Value, suitedness: Suitedness) -> Vec<[Card; 2]> {
+    let mut combos = Vec::new();
+    for (i, &high_suit) in Suit::suits().iter().enumerate() {
+        for (j, &low_suit) in Suit::suits().iter().enumerate() {
+            // For pairs, only keep each pair of suits once
+            if high == low && j <= i {
+                continue;
+            }
+            if suitedness == Suitedness::Suited && high_suit != low_suit {
+                continue;
+            }
+            if suitedness == Suitedness::OffSuit && high_suit == low_suit {
+                continue;
+            }
+            combos.push([Card::new(high, high_suit), Card::new(low, low_suit)]);
+        }
+    }
+    combos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_suited() {
+        let range = Range::parse("AKs").unwrap();
+        assert_eq!(range.len(), 4);
+        for combo in range.combos {
+            assert_eq!(combo.cards[0].suit, combo.cards[1].suit);
+        }
+    }
+
+    #[test]
+    fn test_parse_offsuit() {
+        assert_eq!(Range::parse("KQo").unwrap().len(), 12);
+    }
+
+    #[test]
+    fn test_parse_any_suits() {
+        assert_eq!(Range::parse("KQ").unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_parse_pair_plus() {
+        // TT, JJ, QQ, KK, AA with 6 combos each
+        assert_eq!(Range::parse("TT+").unwrap().len(), 30);
+    }
+
+    #[test]
+    fn test_parse_kicker_plus() {
+        // ATs, AJs, AQs, AKs
+        assert_eq!(Range::parse("ATs+").unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_parse_pair_dash() {
+        assert_eq!(Range::parse("22-66").unwrap().len(), 30);
+    }
+
+    #[test]
+    fn test_parse_kicker_dash() {
+        // A2s, A3s, A4s, A5s
+        let range = Range::parse("A2s-A5s").unwrap();
+        assert_eq!(range.len(), 16);
+        assert_eq!(range, Range::parse("A5s-A2s").unwrap());
+    }
+
+    #[test]
+    fn test_parse_specific_combo() {
+        let range = Range::parse("AhKd").unwrap();
+        assert_eq!(range.len(), 1);
+        assert_eq!(range.combos[0].cards[0], Card::new(Value::Ace, Suit::Heart));
+    }
+
+    #[test]
+    fn test_parse_weights() {
+        let range = Range::parse("AKo:0.5, QQ").unwrap();
+        assert_eq!(range.len(), 18);
+        assert_eq!(range.combos[0].weight, 0.5);
+        assert_eq!(range.combos[17].weight, 1.0);
+    }
+
+    #[test]
+    fn test_parse_duplicates_keep_last_weight() {
+        let range = Range::parse("AA, AA:0.25").unwrap();
+        assert_eq!(range.len(), 6);
+        assert!(range.combos.iter().all(|combo| combo.weight == 0.25));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Range::parse("").is_err());
+        assert!(Range::parse("AAs").is_err());
+        assert!(Range::parse("AKx").is_err());
+        assert!(Range::parse("AK:2").is_err());
+        assert!(Range::parse("AKs-QJs").is_err());
+        assert!(Range::parse("AKs-A2o").is_err());
+    }
+
+    // Aces on the board and in our hand leave only one combo of aces
+    #[test]
+    fn test_card_removal() {
+        let range = Range::parse("AA").unwrap();
+        let dead_cards: Vec<Card> = Hand::new_from_str("AhAd").unwrap().cards().to_vec();
+        assert_eq!(range.remove_cards(&dead_cards).len(), 1);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::calc_prob::{self, Street};
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::report::{analyze, AnalyzeOptions, Report};
+use rs_poker::core::{Card, Hand, Rankable};
+
+/// One line typed in the REPL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `hand AhKh`: set our hole cards.
+    Hand(Vec<Card>),
+    /// `board 7h6h2c` or `board 7h`: add community cards.
+    Board(Vec<Card>),
+    /// `dead 2c`: add cards that can't come anymore.
+    Dead(Vec<Card>),
+    /// `allin`: switch between dealing the next street and every remaining card.
+    AllIn,
+    /// `undo`: take back the last change.
+    Undo,
+    /// `reset`: start a new hand, undo brings the last one back.
+    Reset,
+    /// `show`: print the analysis again.
+    Show,
+    Help,
+    Quit,
+}
+
+// Function to parse a line of the REPL, the command is not case sensitive
+pub fn parse_command(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    let (name, cards_str) = line.split_once(' ').unwrap_or((line, ""));
+    let parse = |cards_str: &str| -> Result<Vec<Card>, String> {
+        let cards = calc_prob::parse_cards(cards_str).map_err(|err| err.to_string())?;
+        if cards.is_empty() {
+            return Err(format!("'{}' needs at least one card", name));
+        }
+        Ok(cards.cards().to_vec())
+    };
+
+    match name.to_lowercase().as_str() {
+        "hand" => parse(cards_str).map(Command::Hand),
+        "board" => parse(cards_str).map(Command::Board),
+        "dead" => parse(cards_str).map(Command::Dead),
+        "allin" => Ok(Command::AllIn),
+        "undo" => Ok(Command::Undo),
+        "reset" => Ok(Command::Reset),
+        "show" | "" => Ok(Command::Show),
+        "help" | "?" => Ok(Command::Help),
+        "quit" | "exit" | "q" => Ok(Command::Quit),
+        _ => Err(format!(
+            "Unknown command '{}', type help for the commands",
+            name
+        )),
+    }
+}
+
+/// Hand followed street by street, every change can be undone.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Session {
+    pub game: Game,
+    pub hand: Vec<Card>,
+    pub board: Vec<Card>,
+    pub dead_cards: Vec<Card>,
+    pub all_in: bool,
+    // State before each change, the last one is restored by undo
+    history: Vec<Snapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Snapshot {
+    hand: Vec<Card>,
+    board: Vec<Card>,
+    dead_cards: Vec<Card>,
+    all_in: bool,
+}
+
+impl Session {
+    pub fn new(game: Game, hand: Vec<Card>) -> Session {
+        Session {
+            game,
+            hand,
+            ..Session::default()
+        }
+    }
+
+    // Function to apply a command that changes the state, the state is kept when the command is not valid
+    pub fn apply(&mut self, command: &Command) -> Result<(), PokerProbError> {
+        let previous = self.snapshot();
+
+        match command {
+            Command::Hand(cards) => {
+                self.game
+                    .check_hole_cards(&Hand::new_with_cards(cards.clone()))?;
+                self.hand = cards.clone();
+            }
+            Command::Board(cards) => {
+                if self.board.len() + cards.len() > 5 {
+                    return Err(PokerProbError::InvalidBoardSize(
+                        self.board.len() + cards.len(),
+                    ));
+                }
+                self.board.extend_from_slice(cards);
+            }
+            Command::Dead(cards) => self.dead_cards.extend_from_slice(cards),
+            Command::AllIn => self.all_in = !self.all_in,
+            Command::Undo => {
+                if let Some(snapshot) = self.history.pop() {
+                    self.restore(snapshot);
+                }
+                return Ok(());
+            }
+            // The history is kept, so a hand reset by mistake can be brought back with undo
+            Command::Reset => self.restore(Snapshot {
+                hand: Vec::new(),
+                board: Vec::new(),
+                dead_cards: Vec::new(),
+                all_in: false,
+            }),
+            Command::Show | Command::Help | Command::Quit => return Ok(()),
+        }
+
+        // A card can only be used once between the hand, the board and the dead cards
+        if let Err(err) = self.check_duplicates() {
+            self.restore(previous);
+            return Err(err);
+        }
+        self.history.push(previous);
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            hand: self.hand.clone(),
+            board: self.board.clone(),
+            dead_cards: self.dead_cards.clone(),
+            all_in: self.all_in,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.hand = snapshot.hand;
+        self.board = snapshot.board;
+        self.dead_cards = snapshot.dead_cards;
+        self.all_in = snapshot.all_in;
+    }
+
+    fn check_duplicates(&self) -> Result<(), PokerProbError> {
+        let mut seen: Vec<Card> = Vec::new();
+        for card in self.hand.iter().chain(&self.board).chain(&self.dead_cards) {
+            if seen.contains(card) {
+                return Err(PokerProbError::DuplicateCard(*card));
+            }
+            seen.push(*card);
+        }
+        Ok(())
+    }
+
+    // Function to know whether the board is a street, e.g. not while the flop is entered one card at a time
+    pub fn street(&self) -> Option<Street> {
+        Street::from_num_community_cards(self.board.len()).ok()
+    }
+
+    // Function to recompute the outs and probabilities of the current state
+    pub fn analyze(&self) -> Result<Report, PokerProbError> {
+        let options = AnalyzeOptions {
+            game: self.game,
+            all_in: self.all_in,
+            dead_cards: self.dead_cards.clone(),
+            ..AnalyzeOptions::default()
+        };
+        analyze(
+            &Hand::new_with_cards(self.hand.clone()),
+            &Hand::new_with_cards(self.board.clone()),
+            &options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(cards_str: &str) -> Vec<Card> {
+        calc_prob::parse_cards(cards_str).unwrap().cards().to_vec()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("board 7h"), Ok(Command::Board(cards("7h"))));
+        assert_eq!(
+            parse_command("Hand Ah Kh"),
+            Ok(Command::Hand(cards("AhKh")))
+        );
+        assert_eq!(parse_command("dead 2c3c"), Ok(Command::Dead(cards("2c3c"))));
+        assert_eq!(parse_command("undo"), Ok(Command::Undo));
+        assert_eq!(parse_command(""), Ok(Command::Show));
+        assert!(parse_command("board").is_err());
+        assert!(parse_command("board 7x").is_err());
+        assert!(parse_command("fold").is_err());
+    }
+
+    // The flop entered one card at a time is only analyzed once it is complete
+    #[test]
+    fn test_board_card_by_card() {
+        let mut session = Session::new(Game::Holdem, cards("9h8h"));
+        assert_eq!(session.street(), Some(Street::Preflop));
+        session.apply(&Command::Board(cards("7h"))).unwrap();
+        session.apply(&Command::Board(cards("6h"))).unwrap();
+        assert_eq!(session.street(), None);
+        session.apply(&Command::Board(cards("2c"))).unwrap();
+        assert_eq!(session.street(), Some(Street::Flop));
+        assert_eq!(session.analyze().unwrap().street, Street::Flop);
+    }
+
+    #[test]
+    fn test_undo_and_reset() {
+        let mut session = Session::new(Game::Holdem, cards("9h8h"));
+        session.apply(&Command::Board(cards("7h6h2c"))).unwrap();
+        session.apply(&Command::Dead(cards("Th"))).unwrap();
+        session.apply(&Command::Undo).unwrap();
+        assert!(session.dead_cards.is_empty());
+        session.apply(&Command::Undo).unwrap();
+        assert!(session.board.is_empty());
+        // Nothing left to undo
+        session.apply(&Command::Undo).unwrap();
+        assert_eq!(session.hand, cards("9h8h"));
+
+        session.apply(&Command::Reset).unwrap();
+        assert!(session.hand.is_empty());
+        assert_eq!(session.game, Game::Holdem);
+
+        // The reset can be undone like any other change
+        let mut session = Session::new(Game::Holdem, cards("9h8h"));
+        session.apply(&Command::Board(cards("7h6h2c"))).unwrap();
+        session.apply(&Command::AllIn).unwrap();
+        session.apply(&Command::Reset).unwrap();
+        assert!(session.hand.is_empty() && session.board.is_empty() && !session.all_in);
+        session.apply(&Command::Undo).unwrap();
+        assert_eq!(session.hand, cards("9h8h"));
+        assert_eq!(session.board, cards("7h6h2c"));
+        assert!(session.all_in);
+
+        // A new hand is still played in the same game
+        let mut session = Session::new(Game::Omaha, cards("AhKhQh2h"));
+        session.apply(&Command::Reset).unwrap();
+        assert_eq!(session.game, Game::Omaha);
+        assert!(session.hand.is_empty());
+    }
+
+    // A card already used is rejected and the state is kept
+    #[test]
+    fn test_duplicate_card() {
+        let mut session = Session::new(Game::Holdem, cards("9h8h"));
+        session.apply(&Command::Board(cards("7h6h2c"))).unwrap();
+        assert_eq!(
+            session.apply(&Command::Dead(cards("7h"))),
+            Err(PokerProbError::DuplicateCard(cards("7h")[0]))
+        );
+        assert!(session.dead_cards.is_empty());
+        assert_eq!(
+            session.apply(&Command::Board(cards("5h4h3h"))),
+            Err(PokerProbError::InvalidBoardSize(6))
+        );
+    }
+
+    #[test]
+    fn test_dead_cards_are_not_outs() {
+        let mut session = Session::new(Game::Holdem, cards("9h8h"));
+        session.apply(&Command::Board(cards("7h6h2c"))).unwrap();
+        session.apply(&Command::Dead(cards("Th5h"))).unwrap();
+        let report = session.analyze().unwrap();
+        assert!(report
+            .hand_ranks
+            .iter()
+            .all(|hand_rank_report| !hand_rank_report.outs.contains(&cards("Th")[0])));
+    }
+}
@@ -0,0 +1,525 @@
+use crate::calc_prob::{self, HandRank, MadeHand, StraightDraw, Street};
+use crate::draw::{self, DrawReport};
+use crate::equity::{self, Equity};
+use crate::error::PokerProbError;
+use crate::game::Game;
+use crate::low::{self, LowReport};
+use crate::pot_odds::{self, PotOdds};
+use crate::preflop::{self, PreflopReport};
+use crate::range::Range;
+use crate::true_outs::{self, TrueOuts};
+use rs_poker::core::{Card, Hand, Rankable, Value};
+use std::collections::BTreeMap;
+
+/// What to calculate next to the outs of our hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzeOptions {
+    pub game: Game,
+    /// Deal every remaining community card instead of only the next street.
+    pub all_in: bool,
+    /// Hands or ranges of the opponents, for true outs and equity.
+    pub villains: Vec<Range>,
+    /// Number of runouts to simulate for equity.
+    pub iterations: u32,
+    /// Seed of the equity simulation.
+    pub seed: u64,
+    /// Size of the pot, including the bet to call.
+    pub pot: Option<f64>,
+    /// Size of the bet to call, the pot odds are only calculated with both the pot and the bet.
+    pub bet: Option<f64>,
+    /// Effective stack before calling, for implied odds.
+    pub effective_stack: Option<f64>,
+    /// Cards seen elsewhere, e.g. folded by other players, that can't come anymore.
+    pub dead_cards: Vec<Card>,
+    /// Hand rank to aim for with the discards in draw, the one above the made hand by default.
+    pub draw_target: Option<HandRank>,
+}
+
+impl Default for AnalyzeOptions {
+    fn default() -> Self {
+        AnalyzeOptions {
+            game: Game::Holdem,
+            all_in: false,
+            villains: Vec::new(),
+            iterations: 10000,
+            seed: 0,
+            pot: None,
+            bet: None,
+            effective_stack: None,
+            dead_cards: Vec::new(),
+            draw_target: None,
+        }
+    }
+}
+
+/// Outs and probabilities of finishing with one hand rank.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandRankReport {
+    pub hand_rank: HandRank,
+    /// Cards that make this hand rank on the next card, empty preflop and on the river.
+    pub outs: Vec<Card>,
+    /// Probability with the rule of 4 and 2, in percent.
+    pub four_and_two_prob: i8,
+    /// Probability from going through every runout, in percent.
+    pub exact_prob: f64,
+}
+
+/// Everything calculated for a hand and a board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub game: Game,
+    pub hand: Vec<Card>,
+    pub board: Vec<Card>,
+    pub dead_cards: Vec<Card>,
+    /// Cards left in the deck once our hand, the board and the dead cards are removed.
+    pub num_unknown_cards: usize,
+    pub street: Street,
+    /// Hand rank made with the cards on the table.
+    pub current_hand_rank: HandRank,
+    /// Best 5 cards on the table with their kickers.
+    pub made_hand: MadeHand,
+    /// One entry per hand rank, from the weakest to the strongest in the order of the game.
+    pub hand_ranks: Vec<HandRankReport>,
+    /// Every unknown card that improves our hand, under the best hand rank it makes.
+    pub combined_outs: BTreeMap<HandRank, Vec<Card>>,
+    /// Probability to improve with the rule of 4 and 2 on the unique outs, in percent.
+    pub improve_prob: i8,
+    /// Exact probability to finish with a better hand rank, in percent.
+    pub exact_improve_prob: f64,
+    pub straight_draw: StraightDraw,
+    /// Values that complete the straight draw.
+    pub straight_values: Vec<Value>,
+    pub pot_odds: Option<PotOdds>,
+    /// Number of combos of each opponent after card removal.
+    pub villain_combos: Vec<usize>,
+    /// Outs checked against the opponents, only on the flop and the turn.
+    pub true_outs: Option<TrueOuts>,
+    /// Equity against the opponents, only when there are opponents.
+    pub equity: Option<Equity>,
+    /// Discard options, only in draw.
+    pub draw: Option<DrawReport>,
+    /// Low half of the pot, only in hi/lo games.
+    pub low: Option<LowReport>,
+    /// Strength of the starting hand, only preflop in hold'em.
+    pub preflop: Option<PreflopReport>,
+}
+
+// Function to check that the equity simulation deals at least one runout
+pub fn check_iterations(iterations: u32) -> Result<(), PokerProbError> {
+    if iterations == 0 {
+        return Err(PokerProbError::InvalidOption(
+            "Iterations should be at least 1, got 0".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// Function to check the cards and calculate the outs, probabilities and equity of our hand
+pub fn analyze(
+    hand: &Hand,
+    board: &Hand,
+    options: &AnalyzeOptions,
+) -> Result<Report, PokerProbError> {
+    let game = options.game;
+    check_iterations(options.iterations)?;
+    pot_odds::check_sizes(options.pot, options.bet, options.effective_stack)?;
+    game.check_hole_cards(hand)?;
+    let street = game.street(hand, board)?;
+    // The ranges of the opponents are made of 2 card combos
+    if game != Game::Holdem && !options.villains.is_empty() {
+        return Err(PokerProbError::InvalidRange(format!(
+            "Opponent ranges are only supported in {}",
+            Game::Holdem
+        )));
+    }
+    let deck = calc_prob::get_unknown_cards(game, hand, board, &options.dead_cards)?;
+    let exact_probs = calc_prob::calc_exact_probs(game, options.all_in, &deck, hand, board);
+    let made_hand = calc_prob::get_made_hand(game, hand, board);
+    let current_hand_rank = made_hand.hand_rank;
+
+    let has_outs = street.has_outs();
+    // Stud deals up to 4 more cards, the rule of 2 is applied to each of them
+    let num_cards_to_come = game.num_cards_to_come(options.all_in, hand, board);
+    let four_and_two_prob = |outs: usize| {
+        let outs = i32::try_from(outs).unwrap_or(i32::MAX);
+        if game == Game::Stud {
+            calc_prob::calc_rule_of_2_probs(num_cards_to_come, outs)
+        } else {
+            calc_prob::calc_4_and_2_probs(options.all_in, board.len(), outs)
+        }
+    };
+
+    let hand_ranks: Vec<HandRankReport> = game
+        .hand_ranks()
+        .into_iter()
+        .map(|hand_rank| {
+            let outs = if has_outs {
+                game.calc_outs(hand_rank, &deck, hand, board)
+            } else {
+                Vec::new()
+            };
+            HandRankReport {
+                hand_rank,
+                four_and_two_prob: four_and_two_prob(outs.len()),
+                exact_prob: exact_probs.get(&hand_rank).copied().unwrap_or(0.0),
+                outs,
+            }
+        })
+        .collect();
+
+    let combined_outs = if has_outs {
+        calc_prob::calc_combined_outs(game, &deck, hand, board)
+    } else {
+        BTreeMap::new()
+    };
+    let num_unique_outs: usize = combined_outs.values().map(|cards| cards.len()).sum();
+    let improve_prob = four_and_two_prob(num_unique_outs);
+    let exact_improve_prob: f64 = exact_probs
+        .iter()
+        .filter(|(&hand_rank, _)| game.strength(hand_rank) > game.strength(current_hand_rank))
+        .map(|(_, prob)| prob)
+        .sum();
+
+    let (straight_draw, straight_values) = game.straight_draw(hand, board);
+
+    let preflop = if game == Game::Holdem && street == Street::Preflop {
+        preflop::calc_preflop(hand)
+    } else {
+        None
+    };
+
+    let low = if game == Game::OmahaHiLo {
+        Some(low::calc_low(
+            game,
+            options.all_in,
+            &deck,
+            hand,
+            board,
+            has_outs,
+        ))
+    } else {
+        None
+    };
+
+    let draw = if street == Street::Draw {
+        let target = options
+            .draw_target
+            .unwrap_or_else(|| draw::default_target(game, current_hand_rank));
+        Some(DrawReport {
+            target,
+            options: draw::calc_discard_options(game, &deck, hand, target),
+        })
+    } else {
+        None
+    };
+
+    let pot_odds = match (options.pot, options.bet) {
+        (Some(pot), Some(bet)) if has_outs => Some(pot_odds::calc_pot_odds(
+            pot,
+            bet,
+            options.effective_stack,
+            exact_improve_prob,
+        )),
+        _ => None,
+    };
+
+    // The opponents can't hold a dead card either
+    let villains: Vec<Range> = options
+        .villains
+        .iter()
+        .map(|range| range.remove_cards(&options.dead_cards))
+        .collect();
+    let mut known_cards: Vec<Card> = hand.cards().to_vec();
+    known_cards.extend_from_slice(board.cards());
+    let villain_combos = villains
+        .iter()
+        .map(|range| range.remove_cards(&known_cards).len())
+        .collect();
+
+    let (true_outs, equity) = if villains.is_empty() {
+        (None, None)
+    } else {
+        let true_outs = if has_outs {
+            Some(true_outs::calc_true_outs(&deck, hand, board, &villains))
+        } else {
+            None
+        };
+        let equity = equity::calc_equity(
+            hand,
+            board,
+            &options.dead_cards,
+            &villains,
+            options.iterations,
+            options.seed,
+        )?;
+        (true_outs, Some(equity))
+    };
+
+    Ok(Report {
+        game,
+        hand: hand.cards().to_vec(),
+        board: board.cards().to_vec(),
+        dead_cards: options.dead_cards.clone(),
+        num_unknown_cards: deck.len(),
+        street,
+        current_hand_rank,
+        made_hand,
+        hand_ranks,
+        combined_outs,
+        improve_prob,
+        exact_improve_prob,
+        straight_draw,
+        straight_values,
+        pot_odds,
+        villain_combos,
+        true_outs,
+        equity,
+        draw,
+        low,
+        preflop,
+    })
+}
+
+impl Report {
+    // Function to list the unique outs from the best hand rank of the game down
+    pub fn combined_outs_best_first(&self) -> Vec<(HandRank, &Vec<Card>)> {
+        let mut combined_outs: Vec<(HandRank, &Vec<Card>)> = self
+            .combined_outs
+            .iter()
+            .map(|(&hand_rank, cards)| (hand_rank, cards))
+            .collect();
+        combined_outs
+            .sort_by_key(|(hand_rank, _)| std::cmp::Reverse(self.game.strength(*hand_rank)));
+        combined_outs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rs_poker::core::Suit;
+
+    #[test]
+    fn test_analyze_flop() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let report = analyze(&hand, &board, &AnalyzeOptions::default()).unwrap();
+
+        assert_eq!(report.street, Street::Flop);
+        assert_eq!(report.current_hand_rank, HandRank::HighCard);
+        assert_eq!(report.hand_ranks.len(), 10);
+        assert_eq!(report.combined_outs[&HandRank::StraightFlush].len(), 2);
+        assert_eq!(report.straight_draw, StraightDraw::OpenEnded);
+        assert_eq!(report.equity, None);
+    }
+
+    // Preflop and on the river there are no outs, only the exact probabilities
+    #[test]
+    fn test_analyze_river() {
+        let hand = Hand::new_from_str("AhAd").unwrap();
+        let board = Hand::new_from_str("7h6c2cAs3d").unwrap();
+        let report = analyze(&hand, &board, &AnalyzeOptions::default()).unwrap();
+
+        assert_eq!(report.street, Street::River);
+        assert_eq!(report.current_hand_rank, HandRank::ThreeOfAKind);
+        assert_eq!(report.made_hand.hand_rank, HandRank::ThreeOfAKind);
+        assert_eq!(report.made_hand.kickers.len(), 2);
+        assert!(report.combined_outs.is_empty());
+        assert!(report
+            .hand_ranks
+            .iter()
+            .all(|hand_rank_report| hand_rank_report.outs.is_empty()));
+    }
+
+    #[test]
+    fn test_analyze_with_villains_and_pot_odds() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let options = AnalyzeOptions {
+            villains: vec![Range::parse("2s2d").unwrap()],
+            iterations: 500,
+            pot: Some(100.0),
+            bet: Some(50.0),
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+
+        assert_eq!(report.villain_combos, vec![1]);
+        assert!(report.true_outs.is_some());
+        assert!(report.equity.is_some());
+        assert!(report.pot_odds.is_some());
+    }
+
+    // A dead card is not an out anymore
+    #[test]
+    fn test_analyze_dead_cards() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let options = AnalyzeOptions {
+            dead_cards: vec![Card::try_from("Th").unwrap()],
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        assert_eq!(report.num_unknown_cards, 46);
+        assert_eq!(
+            report.combined_outs[&HandRank::StraightFlush],
+            vec![Card::try_from("5h").unwrap()]
+        );
+
+        let options = AnalyzeOptions {
+            dead_cards: vec![Card::try_from("9h").unwrap()],
+            ..AnalyzeOptions::default()
+        };
+        assert_eq!(
+            analyze(&hand, &board, &options),
+            Err(PokerProbError::DuplicateCard(Card::try_from("9h").unwrap()))
+        );
+    }
+
+    // Only 2 of the 4 hearts in hand can be used, a heart on the board is not a flush draw anymore
+    #[test]
+    fn test_analyze_omaha() {
+        let hand = Hand::new_from_str("AhKhQh2h").unwrap();
+        let board = Hand::new_from_str("Jh7c2c").unwrap();
+        let options = AnalyzeOptions {
+            game: Game::Omaha,
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        assert_eq!(report.game, Game::Omaha);
+        assert_eq!(report.current_hand_rank, HandRank::OnePair);
+        assert!(!report.combined_outs.contains_key(&HandRank::Flush));
+        assert_eq!(report.straight_draw, StraightDraw::NoDraw);
+
+        assert_eq!(
+            analyze(&hand, &board, &AnalyzeOptions::default()),
+            Err(PokerProbError::InvalidHoleCards(Game::Holdem, 4))
+        );
+    }
+
+    // 6 card Omaha can have more than 25 unique outs, the rule of 4 stops at 100%
+    #[test]
+    fn test_analyze_six_card_omaha() {
+        let hand = Hand::new_from_str("AhKhQc9s8c7d").unwrap();
+        let board = Hand::new_from_str("Jh6h2d3s").unwrap();
+        let options = AnalyzeOptions {
+            game: Game::Omaha,
+            all_in: true,
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        let num_unique_outs: usize = report.combined_outs.values().map(|cards| cards.len()).sum();
+        assert_eq!(num_unique_outs, 33);
+        assert_eq!(report.improve_prob, 100);
+        assert!(report
+            .hand_ranks
+            .iter()
+            .all(|hand_rank_report| (0..=100).contains(&hand_rank_report.four_and_two_prob)));
+    }
+
+    // The flush is listed before the full house in short deck
+    #[test]
+    fn test_analyze_short_deck() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let board = Hand::new_from_str("QhJh6c").unwrap();
+        let options = AnalyzeOptions {
+            game: Game::ShortDeck,
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        assert_eq!(report.game, Game::ShortDeck);
+        assert_eq!(report.num_unknown_cards, 31);
+        assert_eq!(report.straight_draw, StraightDraw::Broadway);
+        assert_eq!(report.straight_values, vec![Value::Ten]);
+        let best_first: Vec<HandRank> = report
+            .combined_outs_best_first()
+            .into_iter()
+            .map(|(hand_rank, _)| hand_rank)
+            .collect();
+        let flush = best_first.iter().position(|&r| r == HandRank::Flush);
+        let full_house = best_first.iter().position(|&r| r == HandRank::FullHouse);
+        assert!(flush.is_some());
+        assert!(full_house.is_none() || flush < full_house);
+
+        let hand = Hand::new_from_str("Ah5h").unwrap();
+        assert_eq!(
+            analyze(&hand, &board, &options),
+            Err(PokerProbError::CardNotInDeck(
+                Game::ShortDeck,
+                Card::new(Value::Five, Suit::Heart)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_analyze_no_iterations() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let board = Hand::new_from_str("7h6h2c").unwrap();
+        let options = AnalyzeOptions {
+            villains: vec![Range::parse("2s2d").unwrap()],
+            iterations: 0,
+            ..AnalyzeOptions::default()
+        };
+        assert!(matches!(
+            analyze(&hand, &board, &options),
+            Err(PokerProbError::InvalidOption(_))
+        ));
+    }
+
+    #[test]
+    fn test_analyze_invalid_board() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let board = Hand::new_from_str("7h6h").unwrap();
+        assert_eq!(
+            analyze(&hand, &board, &AnalyzeOptions::default()),
+            Err(PokerProbError::InvalidBoardSize(2))
+        );
+    }
+
+    // Stud outs on 4th street, with the rule of 2 on each card to come when all in
+    #[test]
+    fn test_analyze_stud() {
+        let hand = Hand::new_from_str("9h8h7h6c").unwrap();
+        let options = AnalyzeOptions {
+            game: Game::Stud,
+            all_in: true,
+            dead_cards: Hand::new_from_str("Th5h2h6s").unwrap().cards().to_vec(),
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &Hand::default(), &options).unwrap();
+        assert_eq!(report.street, Street::FourthStreet);
+        assert_eq!(report.num_unknown_cards, 44);
+        let straight = &report.hand_ranks[HandRank::Straight as usize];
+        assert_eq!(straight.outs.len(), 6);
+        assert_eq!(straight.four_and_two_prob, 36);
+        assert!(straight.exact_prob > 30.0);
+
+        let hand = Hand::new_from_str("9h8h7h6c2d3d4s").unwrap();
+        let report = analyze(&hand, &Hand::default(), &options).unwrap();
+        assert_eq!(report.street, Street::SeventhStreet);
+        assert!(report.combined_outs.is_empty());
+    }
+
+    // A hi/lo scenario has both halves of the pot, the other games have no low
+    #[test]
+    fn test_analyze_omaha_hi_lo() {
+        let hand = Hand::new_from_str("Ah2dKsKc").unwrap();
+        let board = Hand::new_from_str("3c4h8dQs").unwrap();
+        let options = AnalyzeOptions {
+            game: Game::OmahaHiLo,
+            ..AnalyzeOptions::default()
+        };
+        let report = analyze(&hand, &board, &options).unwrap();
+        assert_eq!(report.current_hand_rank, HandRank::OnePair);
+        assert!(!report.combined_outs.is_empty());
+        let low = report.low.unwrap();
+        assert!(low.is_nut());
+        assert_eq!(low.outs.len(), 12);
+
+        let options = AnalyzeOptions {
+            game: Game::Omaha,
+            ..AnalyzeOptions::default()
+        };
+        assert_eq!(analyze(&hand, &board, &options).unwrap().low, None);
+    }
+}
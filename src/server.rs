@@ -0,0 +1,245 @@
+use crate::error::PokerProbError;
+use crate::json::JsonRequest;
+use std::io::Read;
+use std::sync::Arc;
+use std::thread;
+use tiny_http::{Header, Request, Server};
+
+// A scenario is a few hundred bytes, anything much bigger is not a request for us
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Status code and JSON body to send back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl ApiResponse {
+    fn error(status: u16, message: &str) -> ApiResponse {
+        ApiResponse {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+// Function to answer a request, kept apart from the server so it can be called without a socket
+pub fn handle(method: &str, path: &str, body: &str, default_iterations: u32) -> ApiResponse {
+    // The query string is not used
+    let path = path.split('?').next().unwrap_or_default();
+    let with_villains = match path {
+        "/outs" => false,
+        "/equity" => true,
+        _ => return ApiResponse::error(404, &format!("No endpoint at {}", path)),
+    };
+    if method == "OPTIONS" {
+        return ApiResponse {
+            status: 204,
+            body: String::new(),
+        };
+    }
+    if method != "POST" {
+        return ApiResponse::error(405, &format!("{} only accepts POST", path));
+    }
+
+    let mut request: JsonRequest = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(err) => return ApiResponse::error(400, &format!("Invalid request: {}", err)),
+    };
+    if with_villains && request.scenario.villains.is_empty() {
+        return ApiResponse::error(400, "/equity needs at least one villain");
+    }
+    // The outs don't need the opponents, they are left out so no equity is simulated
+    if !with_villains {
+        request.scenario.villains.clear();
+    }
+    match request.analyze(default_iterations) {
+        Ok(json_report) => ApiResponse {
+            status: 200,
+            body: serde_json::to_string(&json_report).expect("Report should serialize to JSON."),
+        },
+        Err(err) => ApiResponse::error(400, &err.to_string()),
+    }
+}
+
+// Function to answer requests on localhost until the process is stopped. One worker per core takes the
+// requests, so a long simulation doesn't hold up the others and a burst of them waits in the queue
+pub fn serve(port: u16, default_iterations: u32) -> Result<(), PokerProbError> {
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|err| PokerProbError::Io(format!("Can't listen on port {}: {}", port, err)))?;
+    let server = Arc::new(server);
+
+    let num_workers = thread::available_parallelism().map_or(1, |num| num.get());
+    let workers: Vec<_> = (0..num_workers)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    respond(request, default_iterations);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        let _ = worker.join();
+    }
+    Ok(())
+}
+
+// Function to read the body of a request, up to MAX_BODY_BYTES, and send back the answer
+fn respond(mut request: Request, default_iterations: u32) {
+    let too_large = || {
+        ApiResponse::error(
+            413,
+            &format!("The body can't be more than {} bytes", MAX_BODY_BYTES),
+        )
+    };
+    let mut body = String::new();
+    let response = if request.body_length().unwrap_or_default() > MAX_BODY_BYTES {
+        too_large()
+    } else {
+        // The length is not always sent, one byte more than the limit tells the body is too large
+        match request
+            .as_reader()
+            .take(MAX_BODY_BYTES as u64 + 1)
+            .read_to_string(&mut body)
+        {
+            Ok(num_bytes) if num_bytes > MAX_BODY_BYTES => too_large(),
+            Ok(_) => handle(
+                request.method().as_str(),
+                request.url(),
+                &body,
+                default_iterations,
+            ),
+            Err(err) => ApiResponse::error(400, &format!("Can't read the body: {}", err)),
+        }
+    };
+
+    // The trainer is served from another port, the browser needs to be allowed to call us
+    let headers = [
+        "Content-Type: application/json",
+        "Access-Control-Allow-Origin: *",
+        "Access-Control-Allow-Methods: POST, OPTIONS",
+        "Access-Control-Allow-Headers: Content-Type",
+    ];
+    let mut http_response =
+        tiny_http::Response::from_string(response.body).with_status_code(response.status);
+    for header in headers {
+        http_response.add_header(header.parse::<Header>().expect("Header should be valid."));
+    }
+    // The client may be gone, the other requests are still answered
+    let _ = request.respond(http_response);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(response: &ApiResponse) -> serde_json::Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    #[test]
+    fn test_outs() {
+        let response = handle(
+            "POST",
+            "/outs",
+            "{\"hand\": \"9h8h\", \"board\": \"7h6h2c\", \"villains\": [\"2s2d\"]}",
+            100,
+        );
+        assert_eq!(response.status, 200);
+        let report = json(&response);
+        assert_eq!(report["street"], "flop");
+        assert_eq!(report["improve"]["outs"], 29);
+        assert!(report["villains"].is_null());
+    }
+
+    #[test]
+    fn test_equity() {
+        let response = handle(
+            "POST",
+            "/equity",
+            "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"villains\": [\"2s2d\"], \"iterations\": 200, \"seed\": 4}",
+            100,
+        );
+        assert_eq!(response.status, 200);
+        let report = json(&response);
+        assert_eq!(report["villains"]["iterations"], 200);
+        assert_eq!(report["villains"]["seed"], 4);
+        assert!(report["villains"]["equity"]["win"].is_number());
+
+        // Same seed, same equity
+        let again = handle(
+            "POST",
+            "/equity",
+            "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"villains\": [\"2s2d\"], \"iterations\": 200, \"seed\": 4}",
+            100,
+        );
+        assert_eq!(again, response);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(handle("POST", "/odds", "{}", 100).status, 404);
+        assert_eq!(handle("GET", "/outs", "", 100).status, 405);
+        assert_eq!(handle("OPTIONS", "/equity", "", 100).status, 204);
+        assert_eq!(handle("POST", "/outs", "not json", 100).status, 400);
+        assert_eq!(
+            handle("POST", "/equity", "{\"hand\": \"AhKh\"}", 100).status,
+            400
+        );
+
+        let response = handle("POST", "/outs", "{\"hand\": \"AhAh\"}", 100);
+        assert_eq!(response.status, 400);
+        assert_eq!(json(&response)["error"], "Ah is used more than once");
+    }
+
+    // Requests that would keep a thread busy for too long are turned down
+    #[test]
+    fn test_expensive_requests() {
+        let response = handle(
+            "POST",
+            "/equity",
+            "{\"hand\": \"AhKh\", \"villains\": [\"2s2d\"], \"iterations\": 1000000000}",
+            100,
+        );
+        assert_eq!(response.status, 400);
+        assert_eq!(
+            json(&response)["error"],
+            "A request runs at most 1000000 iterations, got 1000000000"
+        );
+
+        let response = handle(
+            "POST",
+            "/outs",
+            "{\"game\": \"omaha\", \"hand\": \"AhKhQd2c\", \"all_in\": true}",
+            100,
+        );
+        assert_eq!(response.status, 400);
+
+        // Only the flop is dealt when not all in
+        let response = handle(
+            "POST",
+            "/outs",
+            "{\"game\": \"omaha\", \"hand\": \"AhKhQd2c\"}",
+            100,
+        );
+        assert_eq!(response.status, 200);
+    }
+
+    #[test]
+    fn test_invalid_pot_odds() {
+        let response = handle(
+            "POST",
+            "/outs",
+            "{\"hand\": \"AhKh\", \"board\": \"7h6h2c\", \"pot\": 100, \"bet\": -50}",
+            100,
+        );
+        assert_eq!(response.status, 400);
+        assert_eq!(
+            json(&response)["error"],
+            "Bet should be more than 0, got -50"
+        );
+    }
+}
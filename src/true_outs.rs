@@ -0,0 +1,171 @@
+use crate::calc_prob;
+use crate::game::Game;
+use crate::range::Range;
+use rs_poker::core::{Card, Deck, Hand, Rankable};
+
+/// Outs checked against the hands of the opponents.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrueOuts {
+    /// Outs that put us ahead of every hand the opponents can hold.
+    pub clean_outs: Vec<Card>,
+    /// Outs that also help some of the opponents' hands, with the share we are still ahead of.
+    pub dirty_outs: Vec<(Card, f64)>,
+    /// Every clean out counts as 1, every dirty out as the share of hands we are ahead of.
+    pub discounted_outs: f64,
+}
+
+// Function to check every out against the hands or ranges of the opponents.
+// An out is clean when the card puts us ahead of every combo they can hold, ties count as half.
+pub fn calc_true_outs(deck: &Deck, hand: &Hand, community: &Hand, villains: &[Range]) -> TrueOuts {
+    let mut known_cards: Vec<Card> = hand.cards().to_vec();
+    known_cards.extend_from_slice(community.cards());
+
+    // Card removal against our hand and the board
+    let villains: Vec<Range> = villains
+        .iter()
+        .map(|range| range.remove_cards(&known_cards))
+        .collect();
+
+    let mut true_outs = TrueOuts {
+        clean_outs: Vec::new(),
+        dirty_outs: Vec::new(),
+        discounted_outs: 0.0,
+    };
+
+    let combined_outs = calc_prob::calc_combined_outs(Game::Holdem, deck, hand, community);
+    let mut outs: Vec<Card> = combined_outs.into_values().flatten().collect();
+    outs.sort_by_key(|card| (card.value, card.suit.to_char()));
+
+    for card in outs {
+        let mut board: Vec<Card> = community.cards().to_vec();
+        board.push(card);
+
+        let mut all_cards: Vec<Card> = hand.cards().to_vec();
+        all_cards.extend_from_slice(&board);
+        let my_rank = all_cards.rank();
+
+        // Share of the hands of all the opponents we are ahead of with this card, None when the card can't come
+        let mut ahead_share = Some(1.0);
+        for range in &villains {
+            let mut total_weight = 0.0;
+            let mut ahead_weight = 0.0;
+            for combo in &range.combos {
+                if combo.cards.contains(&card) {
+                    continue;
+                }
+                all_cards = combo.cards.to_vec();
+                all_cards.extend_from_slice(&board);
+                let villain_rank = all_cards.rank();
+
+                total_weight += combo.weight;
+                if my_rank > villain_rank {
+                    ahead_weight += combo.weight;
+                } else if my_rank == villain_rank {
+                    ahead_weight += combo.weight / 2.0;
+                }
+            }
+
+            ahead_share = match ahead_share {
+                Some(share) if total_weight > 0.0 => Some(share * ahead_weight / total_weight),
+                _ => None,
+            };
+        }
+
+        match ahead_share {
+            Some(share) if share >= 1.0 => {
+                true_outs.clean_outs.push(card);
+                true_outs.discounted_outs += 1.0;
+            }
+            // A card that leaves us behind every combo is not an out
+            Some(share) if share > 0.0 => {
+                true_outs.dirty_outs.push((card, share));
+                true_outs.discounted_outs += share;
+            }
+            _ => {}
+        }
+    }
+
+    true_outs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Flush draw against a set: the flush card that pairs the board gives the set quads, it is no out
+    #[test]
+    fn test_flush_outs_against_set() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let villains = vec![Range::parse("2s2d").unwrap()];
+        let true_outs = calc_true_outs(&deck, &hand, &community, &villains);
+
+        let two = Card::try_from("2h").unwrap();
+        assert!(true_outs.dirty_outs.iter().all(|(card, _)| *card != two));
+        assert!(!true_outs.clean_outs.contains(&two));
+        assert!(true_outs
+            .clean_outs
+            .contains(&Card::try_from("3h").unwrap()));
+        // A and K pair us, but that does not beat a set
+        assert!(!true_outs
+            .clean_outs
+            .contains(&Card::try_from("As").unwrap()));
+        assert!(true_outs.discounted_outs < 9.0);
+    }
+
+    // Against a weaker hand that can't improve with the card, all outs are clean
+    #[test]
+    fn test_clean_outs() {
+        let hand = Hand::new_from_str("9h8h").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let villains = vec![Range::parse("AsAd").unwrap()];
+        let true_outs = calc_true_outs(&deck, &hand, &community, &villains);
+
+        assert!(true_outs
+            .clean_outs
+            .contains(&Card::try_from("Tc").unwrap()));
+        assert!(true_outs
+            .clean_outs
+            .contains(&Card::try_from("3h").unwrap()));
+        // Pairing our 9 still loses to aces, it is no out at all
+        let nine = Card::try_from("9c").unwrap();
+        let dirty_cards: Vec<Card> = true_outs.dirty_outs.iter().map(|(card, _)| *card).collect();
+        assert!(!dirty_cards.contains(&nine));
+        assert!(!true_outs.clean_outs.contains(&nine));
+    }
+
+    // Against a range, an out that only beats part of the range is discounted
+    #[test]
+    fn test_true_outs_against_range() {
+        let hand = Hand::new_from_str("AhKd").unwrap();
+        let community = Hand::new_from_str("Qs7c2h").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let villains = vec![Range::parse("QQ,AQ").unwrap()];
+        let true_outs = calc_true_outs(&deck, &hand, &community, &villains);
+
+        // A king beats AQ but not a set of queens
+        let king_share = true_outs
+            .dirty_outs
+            .iter()
+            .find(|(card, _)| *card == Card::try_from("Kc").unwrap())
+            .map(|(_, share)| *share)
+            .unwrap();
+        assert!(king_share > 0.0 && king_share < 1.0);
+    }
+
+    // A card held by the opponent can't come, so it is not an out
+    #[test]
+    fn test_blocked_out() {
+        let hand = Hand::new_from_str("AhKh").unwrap();
+        let community = Hand::new_from_str("7h6h2c").unwrap();
+        let deck = calc_prob::get_unknown_cards(Game::Holdem, &hand, &community, &[]).unwrap();
+        let villains = vec![Range::parse("QhQd").unwrap()];
+        let true_outs = calc_true_outs(&deck, &hand, &community, &villains);
+
+        let qh = Card::try_from("Qh").unwrap();
+        assert!(!true_outs.clean_outs.contains(&qh));
+        assert!(!true_outs.dirty_outs.iter().any(|(card, _)| *card == qh));
+    }
+}
@@ -0,0 +1,34 @@
+use crate::json::{JsonRequest, Scenario};
+use crate::report::AnalyzeOptions;
+use wasm_bindgen::prelude::*;
+
+// Function for JavaScript to get the JSON report of the outs of a hand, e.g. `outs("9h8h", "7h6h2c", false)`
+#[wasm_bindgen]
+pub fn outs(hand: &str, board: &str, all_in: bool) -> Result<String, JsError> {
+    let request = JsonRequest {
+        scenario: Scenario {
+            hand: hand.to_string(),
+            board: board.to_string(),
+            all_in,
+            ..Scenario::default()
+        },
+        ..JsonRequest::default()
+    };
+    to_json(&request)
+}
+
+// Function for JavaScript to get the JSON report of a request shaped like the body of the HTTP API,
+// e.g. `report('{"hand": "AhKh", "board": "7h6h2c", "villains": ["TT+"]}')`
+#[wasm_bindgen]
+pub fn report(request_json: &str) -> Result<String, JsError> {
+    let request: JsonRequest = serde_json::from_str(request_json)
+        .map_err(|err| JsError::new(&format!("Invalid request: {}", err)))?;
+    to_json(&request)
+}
+
+fn to_json(request: &JsonRequest) -> Result<String, JsError> {
+    let json_report = request
+        .analyze(AnalyzeOptions::default().iterations)
+        .map_err(|err| JsError::new(&err.to_string()))?;
+    Ok(serde_json::to_string(&json_report).expect("Report should serialize to JSON."))
+}